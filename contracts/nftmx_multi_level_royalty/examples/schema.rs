@@ -4,7 +4,7 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use nftmx_multi_level_royalty::{
-  ExecuteMsg, InstantiateMsg, QueryMsg
+  ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg
 };
 
 fn main() {
@@ -16,4 +16,5 @@ fn main() {
   export_schema(&schema_for!(InstantiateMsg), &out_dir);
   export_schema(&schema_for!(ExecuteMsg), &out_dir);
   export_schema(&schema_for!(QueryMsg), &out_dir);
+  export_schema(&schema_for!(MigrateMsg), &out_dir);
 }
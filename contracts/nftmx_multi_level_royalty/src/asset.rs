@@ -1,10 +1,12 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, MessageInfo, QuerierWrapper, StdError, StdResult,
-  Uint128, WasmMsg};
+use std::ops::Deref;
+use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, CustomQuery, Decimal, MessageInfo, QuerierWrapper, QueryRequest,
+  StdError, StdResult, Uint128, WasmMsg};
 use cw20::{Cw20ExecuteMsg};
-use terra_cosmwasm::TerraQuerier;
+use crate::error::ContractError;
+use terra_cosmwasm::{TerraQuery, TerraQueryWrapper, TerraRoute};
 static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -75,7 +77,7 @@ impl Asset{
     if let AssetInfo::NativeToken { denom } = &self.info {
       match message_info.funds.iter().find(|x| x.denom == *denom) {
         Some(coin) => {
-          if self.amount < coin.amount {
+          if coin.amount >= self.amount {
             Ok(())
           } else {
             Err(StdError::generic_err("Native token balance mismatch between the argument and the transferred"))
@@ -94,7 +96,35 @@ impl Asset{
     }
   }
 
-  pub fn into_msg(self, querier: &QuerierWrapper, recipient: Addr) -> StdResult<CosmosMsg> {
+  /// Strict check of the funds paying for this asset: a native asset accepts only its denom
+  /// and at least its amount, a token accepts no funds. Returns the overpayment to refund
+  pub fn assert_sent_funds(&self, message_info: &MessageInfo) -> Result<Uint128, ContractError> {
+    let denom = match &self.info {
+      AssetInfo::NativeToken { denom } => denom,
+      AssetInfo::Token { .. } => {
+        return match message_info.funds.first() {
+          Some(coin) => Err(ContractError::UnexpectedFunds { denom: coin.denom.clone() }),
+          None => Ok(Uint128::zero()),
+        };
+      }
+    };
+    if message_info.funds.is_empty() && !self.amount.is_zero() {
+      return Err(ContractError::NoFunds {});
+    }
+    let sent = message_info.funds.iter().find(|c| c.denom == *denom);
+    if let Some(coin) = message_info.funds.iter().find(|c| c.denom != *denom) {
+      return Err(match sent {
+        Some(_) => ContractError::UnexpectedFunds { denom: coin.denom.clone() },
+        None => ContractError::InvalidDenom { expected: denom.clone(), received: coin.denom.clone() },
+      });
+    }
+    self.assert_sent_native_token_balance(message_info)
+      .map_err(|_| ContractError::InsufficientFunds {})?;
+    let sent = sent.map(|c| c.amount).unwrap_or_default();
+    Ok(sent.checked_sub(self.amount).map_err(StdError::from)?)
+  }
+
+  pub fn into_msg<M>(self, _querier: &QuerierWrapper, recipient: Addr) -> StdResult<CosmosMsg<M>> {
     let amount = self.amount;
 
     match &self.info {
@@ -108,49 +138,144 @@ impl Asset{
       })),
       AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Bank(BankMsg::Send {
         to_address: recipient.to_string(),
-        // amount: vec![self.deduct_tax(querier)?],
         amount: vec![Coin {
           denom: denom.to_string(),
-          amount: amount
+          amount
         }],
 
       })),
     }
   }
 
-  //TODO fix compile error on new cosmwasm_std
-
-  // pub fn deduct_tax(&self, querier: &QuerierWrapper) -> StdResult<Coin> {
-  //   let amount = self.amount;
-  //   if let AssetInfo::NativeToken { denom } = &self.info {
-  //     Ok(Coin {
-  //       denom: denom.to_string(),
-  //       amount: amount.checked_sub(self.compute_tax(querier)?)?,
-  //     })
-  //   } else {
-  //     Err(StdError::generic_err("cannot deduct tax from token asset"))
-  //   }
-  // }
-
-  // pub fn compute_tax(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
-  //   let amount = self.amount;
-  //   if let AssetInfo::NativeToken { denom } = &self.info {
-  //     if denom == "uluna" {
-  //       Ok(Uint128::zero())
-  //     } else {
-  //       let terra_querier = TerraQuerier::new(querier);
-  //       let tax_rate = (terra_querier.query_tax_rate()).rate;
-  //       let tax_cap: Uint128 = (terra_querier.query_tax_cap(denom.to_string())?).cap;
-  //       Ok(std::cmp::min(
-  //         amount.checked_sub(amount.multiply_ratio(
-  //           DECIMAL_FRACTION,
-  //           DECIMAL_FRACTION * tax_rate + DECIMAL_FRACTION,
-  //         ))?,
-  //         tax_cap,
-  //       ))
-  //     }
-  //   } else {
-  //     Ok(Uint128::zero())
-  //   }
-  // }
-}
\ No newline at end of file
+  /// tax paid on top when sending this asset, see `TaxInfo`
+  pub fn compute_tax(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
+    match TaxInfo::for_asset(querier, &self.info)? {
+      Some(tax_info) => tax_info.compute_tax(self.amount),
+      None => Ok(Uint128::zero()),
+    }
+  }
+
+  pub fn deduct_tax(&self, querier: &QuerierWrapper) -> StdResult<Coin> {
+    if let AssetInfo::NativeToken { denom } = &self.info {
+      Ok(Coin {
+        denom: denom.to_string(),
+        amount: self.amount.checked_sub(self.compute_tax(querier)?)?,
+      })
+    } else {
+      Err(StdError::generic_err("cannot deduct tax from token asset"))
+    }
+  }
+}
+
+/// terra-cosmwasm 2.2 is built on cosmwasm-std 0.16, so its `TerraQuerier` can't use our
+/// `QuerierWrapper`. Its query types serialize the same and are sent through this wrapper
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(transparent)]
+struct TerraQueryRequest(TerraQueryWrapper);
+
+impl CustomQuery for TerraQueryRequest {}
+
+#[derive(Deserialize)]
+struct TaxRateResponse {
+  rate: Decimal,
+}
+
+#[derive(Deserialize)]
+struct TaxCapResponse {
+  cap: Uint128,
+}
+
+/// Terra stability tax of a native denom
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaxInfo {
+  pub rate: Decimal,
+  pub cap: Uint128,
+}
+
+impl TaxInfo {
+  /// queries the treasury module, tokens and uluna are not taxed
+  pub fn for_asset(querier: &QuerierWrapper, info: &AssetInfo) -> StdResult<Option<TaxInfo>> {
+    let denom = match info {
+      AssetInfo::NativeToken { denom } if denom != "uluna" => denom,
+      _ => return Ok(None),
+    };
+    let terra_querier = QuerierWrapper::<TerraQueryRequest>::new(querier.deref());
+    let rate: TaxRateResponse = terra_querier.query(&QueryRequest::Custom(TerraQueryRequest(TerraQueryWrapper {
+      route: TerraRoute::Treasury,
+      query_data: TerraQuery::TaxRate {},
+    })))?;
+    let cap: TaxCapResponse = terra_querier.query(&QueryRequest::Custom(TerraQueryRequest(TerraQueryWrapper {
+      route: TerraRoute::Treasury,
+      query_data: TerraQuery::TaxCap { denom: denom.to_string() },
+    })))?;
+    Ok(Some(TaxInfo { rate: rate.rate, cap: cap.cap }))
+  }
+
+  /// the part of `amount` going to tax, so that `amount - tax` can be sent with the tax on top
+  pub fn compute_tax(&self, amount: Uint128) -> StdResult<Uint128> {
+    Ok(std::cmp::min(
+      amount.checked_sub(amount.multiply_ratio(
+        DECIMAL_FRACTION,
+        DECIMAL_FRACTION * self.rate + DECIMAL_FRACTION,
+      ))?,
+      self.cap,
+    ))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::testing::MockQuerier;
+  use cosmwasm_std::{Binary, ContractResult, SystemResult};
+
+  /// a treasury module taxing at `rate` up to `cap` per payout
+  fn treasury(rate: &'static str, cap: u128) -> MockQuerier<TerraQueryRequest> {
+    MockQuerier::new(&[]).with_custom_handler(move |request: &TerraQueryRequest| {
+      let response = match &request.0.query_data {
+        TerraQuery::TaxRate {} => format!(r#"{{"rate":"{}"}}"#, rate),
+        TerraQuery::TaxCap { .. } => format!(r#"{{"cap":"{}"}}"#, cap),
+        query => panic!("unexpected query {:?}", query),
+      };
+      SystemResult::Ok(ContractResult::Ok(Binary::from(response.into_bytes())))
+    })
+  }
+
+  fn uusd(amount: u128) -> Asset {
+    Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::new(amount) }
+  }
+
+  #[test]
+  fn tax_below_the_cap() {
+    let querier = treasury("0.01", 1_000_000);
+    let querier = QuerierWrapper::new(&querier);
+    let tax_info = TaxInfo::for_asset(&querier, &uusd(0).info).unwrap().unwrap();
+    assert_eq!(tax_info, TaxInfo { rate: Decimal::percent(1), cap: Uint128::new(1_000_000) });
+
+    // 1% on top of the 1_000_000 sent
+    let asset = uusd(1_010_000);
+    assert_eq!(asset.compute_tax(&querier).unwrap(), Uint128::new(10_000));
+    assert_eq!(asset.deduct_tax(&querier).unwrap(), Coin::new(1_000_000, "uusd"));
+  }
+
+  #[test]
+  fn tax_is_capped() {
+    let querier = treasury("0.01", 1_000);
+    let querier = QuerierWrapper::new(&querier);
+    let asset = uusd(1_010_000);
+    assert_eq!(asset.compute_tax(&querier).unwrap(), Uint128::new(1_000));
+    assert_eq!(asset.deduct_tax(&querier).unwrap(), Coin::new(1_009_000, "uusd"));
+  }
+
+  #[test]
+  fn luna_and_tokens_are_not_taxed() {
+    let querier = treasury("0.01", 1_000);
+    let querier = QuerierWrapper::new(&querier);
+    let luna = AssetInfo::NativeToken { denom: "uluna".to_string() };
+    let token = AssetInfo::Token { contract_addr: "token".to_string() };
+    assert_eq!(TaxInfo::for_asset(&querier, &luna).unwrap(), None);
+    assert_eq!(TaxInfo::for_asset(&querier, &token).unwrap(), None);
+    let asset = Asset { info: token, amount: Uint128::new(1_010_000) };
+    assert_eq!(asset.compute_tax(&querier).unwrap(), Uint128::zero());
+  }
+}
@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Decimal, StdError, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -27,6 +27,9 @@ pub enum ContractError {
     #[error("already exist pack name")]
     ExistPackName {},
 
+    #[error("pack name must not be empty")]
+    InvalidPackName {},
+
     #[error("not nft pack owner")]
     NotNftOwner {},
 
@@ -45,9 +48,24 @@ pub enum ContractError {
     #[error("Not approved NFT pack")]
     NotNftApproved {},
 
+    #[error("Pack is not for sale")]
+    NotForSale {},
+
+    #[error("Cannot buy your own pack")]
+    CannotBuyOwnPack {},
+
     #[error("Insufficient funds")]
     InsufficientFunds {},
 
+    #[error("No funds sent")]
+    NoFunds {},
+
+    #[error("Expected funds in {expected}, received {received}")]
+    InvalidDenom { expected: String, received: String },
+
+    #[error("Unexpected funds in {denom}")]
+    UnexpectedFunds { denom: String },
+
     #[error("Token pack balance is not enough")]
     NoTokenBalance {},
 
@@ -63,4 +81,127 @@ pub enum ContractError {
     #[error("No the packable token")]
     NoPackableToken {},
 
+    #[error("NFT pack item count exceeds the limit")]
+    PackItemCountExceeded {},
+
+    #[error("Packable NFT supply exceeds the limit")]
+    PackableNftSupplyExceeded {},
+
+    #[error("NFT pack should contain at least one item")]
+    NoPackItems {},
+
+    #[error("Duplicate token id: {token_id}")]
+    DuplicateTokenId { token_id: String },
+
+    #[error("Invalid collection limit")]
+    InvalidCollectionLimit {},
+
+    #[error("Invalid sale denom: {denom}")]
+    InvalidSaleDenom { denom: String },
+
+    #[error("Buy/sell fee can't exceed {max_percent}%")]
+    InvalidBuySellFee { max_percent: u64 },
+
+    #[error("No pending ownership proposal")]
+    NoOwnershipProposal {},
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired {},
+
+    #[error("{operation} is paused")]
+    Paused { operation: String },
+
+    #[error("Minter not found")]
+    MinterNotFound {},
+
+    #[error("Mint quota exceeded")]
+    MintQuotaExceeded {},
+
+    #[error("Mint phase is not active")]
+    MintPhaseNotActive {},
+
+    #[error("Address is not in the mint phase allowlist")]
+    NotAllowlisted {},
+
+    #[error("Mint limit per wallet reached")]
+    WalletMintLimitReached {},
+
+    #[error("No NFT left to mint")]
+    MintQueueEmpty {},
+
+    #[error("Invalid merkle hash: {hash}")]
+    InvalidMerkleHash { hash: String },
+
+    #[error("Merkle proof is missing or invalid")]
+    InvalidMerkleProof {},
+
+    #[error("A hidden drop is already running")]
+    HiddenDropActive {},
+
+    #[error("No hidden drop to reveal")]
+    NoHiddenDrop {},
+
+    #[error("Hidden drop is full")]
+    HiddenDropFull {},
+
+    #[error("Invalid reveal seed commitment")]
+    InvalidSeedCommitment {},
+
+    #[error("Token has no reveal conflict")]
+    NoRevealConflict {},
+
+    #[error("Reveal seed doesn't match the commitment")]
+    InvalidRevealSeed {},
+
+    #[error("Creator shares can't exceed 100%")]
+    InvalidCreatorShares {},
+
+    #[error("Cannot migrate from contract {name}")]
+    InvalidMigrationContract { name: String },
+
+    #[error("Cannot migrate from version {stored} to {current}")]
+    InvalidMigrationVersion { stored: String, current: String },
+
+    #[error("NFT item is not in the pack")]
+    NftNotInPack {},
+
+    #[error("Unable to remove every item from NFT pack")]
+    EmptyNftPack {},
+
+    #[error("Royalty fee can't exceed {max}")]
+    RoyaltyFeeTooHigh { max: Decimal },
+
+    #[error("Royalty fee can only be lowered")]
+    RoyaltyFeeIncrease {},
+
+    #[error("Fee and royalties of {payouts} exceed the sale price of {price}")]
+    PayoutsExceedPrice { price: Uint128, payouts: Uint128 },
+
+    #[error("Fee and royalty rates of the royalty chain add up to {total}, above 100%")]
+    RoyaltyChainTooHigh { total: Decimal },
+
+    #[error("Settlement amount overflow")]
+    SettlementOverflow {},
+
+    #[error("Settlement rate {rate} is above 100%")]
+    InvalidSettlementRate { rate: Decimal },
+
+    #[error("Invalid pack tag {tag}")]
+    InvalidPackTag { tag: String },
+
+    #[error("A pack can't have more than {max} tags")]
+    PackTagCountExceeded { max: usize },
+
+    #[error("Pack {field} can't be longer than {max} bytes")]
+    PackMetadataTooLong { field: String, max: usize },
+
+    #[error("A pack can't have more than {max} attributes")]
+    PackAttributeCountExceeded { max: usize },
+
+    #[error("Delist the pack before changing its items")]
+    PackForSale {},
+
+    #[error("NFTs are only sold within packs")]
+    NftSoldInPacks {},
+
 }
@@ -0,0 +1,399 @@
+//! Typed events for indexers, emitted as `wasm-<type>` next to the `action` attributes.
+//!
+//! Every event carries `schema_version`. Attributes may be added within a version,
+//! renaming or removing one bumps it. Items are identified by `item_type`
+//! (`nft`, `nft_pack` or `token_pack`) and `item_id` (token id or pack id).
+//!
+//! Schema version 1:
+//!
+//! | type           | attributes                                                                 |
+//! |----------------|----------------------------------------------------------------------------|
+//! | `mint`         | item, `owner`, `minter`, `token_uri`, `name`, `price`, `royalty_fee`       |
+//! | `batch_mint`   | `minter`, `count`, `token_ids`, `owners`                                   |
+//! | `burn`         | item, `owner`                                                              |
+//! | `pack`         | item, `owner`, `pack_name`, `price`, `royalty_fee`, `contents`             |
+//! | `pack_items`   | item, `owner`, `added`, `removed`                                          |
+//! | `rename`       | item, `owner`, `old_name`, `name`                                          |
+//! | `pack_metadata` | item, `editor`, `tags`                                                    |
+//! | `unpack`       | item, `owner`                                                              |
+//! | `transfer`     | item, `from`, `to`                                                         |
+//! | `list`         | item, `owner`                                                              |
+//! | `delist`       | item, `owner`                                                              |
+//! | `token_uri`    | item, `token_uri`                                                          |
+//! | `reveal_conflict` | item, `token_uri`                                                       |
+//! | `enqueue`      | `token_ids`, `hidden`                                                      |
+//! | `price_change` | item, `owner`, `old_price`, `new_price`                                    |
+//! | `sale`         | item, `sale_id`, `seller`, `buyer`, `price`, `denom`, `fee`, `tax`, `seller_amount` |
+//! | `royalty_paid` | item, `recipient`, `position`, `amount`, `denom`                           |
+//! | `royalty_update` | item, `owner`, `royalty_fee`, `recipient`                                |
+//!
+//! `delist` also follows a `sale` or `unpack` of a listed pack. `token_uri` sets the revealed URI of a
+//! hidden token, `reveal_conflict` keeps its placeholder because `token_uri` was taken. `enqueue` and
+//! `batch_mint` are the only events without an item. `enqueue` registers queued mints, `hidden` ones take
+//! the next hidden drop indices. `batch_mint` replaces the `mint` events of a batch, `owners` are in
+//! `token_ids` order. `contents` lists the token ids of an NFT pack, or `<amount><token address>` of a token pack.
+//! Token id and tag lists are comma separated. `royalty_paid` follows its `sale`, once per royalty owner
+//! in chain order, with amounts net of tax like the sale's.
+
+use cosmwasm_std::{Addr, Decimal, Event, Uint128};
+
+use crate::msg::SaleItem;
+use crate::settlement::Settlement;
+
+pub const EVENT_SCHEMA_VERSION: &str = "1";
+
+fn event(ty: &str, item: &SaleItem) -> Event {
+    Event::new(ty)
+        .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+        .add_attribute("item_type", item.item_type())
+        .add_attribute("item_id", item.item_id())
+}
+
+pub(crate) fn mint_event(
+    token_id: &str,
+    owner: &Addr,
+    minter: &Addr,
+    token_uri: &str,
+    name: &str,
+    price: Uint128,
+    royalty_fee: Decimal,
+) -> Event {
+    event("mint", &SaleItem::Nft { token_id: token_id.to_string() })
+        .add_attribute("owner", owner)
+        .add_attribute("minter", minter)
+        .add_attribute("token_uri", token_uri)
+        .add_attribute("name", name)
+        .add_attribute("price", price)
+        .add_attribute("royalty_fee", royalty_fee.to_string())
+}
+
+pub(crate) fn burn_event(token_id: &str, owner: &Addr) -> Event {
+    event("burn", &SaleItem::Nft { token_id: token_id.to_string() }).add_attribute("owner", owner)
+}
+
+pub(crate) fn pack_event(
+    item: &SaleItem,
+    owner: &Addr,
+    pack_name: &str,
+    price: Uint128,
+    royalty_fee: Decimal,
+    contents: String,
+) -> Event {
+    event("pack", item)
+        .add_attribute("owner", owner)
+        .add_attribute("pack_name", pack_name)
+        .add_attribute("price", price)
+        .add_attribute("royalty_fee", royalty_fee.to_string())
+        .add_attribute("contents", contents)
+}
+
+pub(crate) fn pack_items_event(pack_id: u64, owner: &Addr, added: &[String], removed: &[String]) -> Event {
+    event("pack_items", &SaleItem::NftPack { pack_id })
+        .add_attribute("owner", owner)
+        .add_attribute("added", added.join(","))
+        .add_attribute("removed", removed.join(","))
+}
+
+pub(crate) fn rename_event(item: &SaleItem, owner: &Addr, old_name: &str, name: &str) -> Event {
+    event("rename", item)
+        .add_attribute("owner", owner)
+        .add_attribute("old_name", old_name)
+        .add_attribute("name", name)
+}
+
+pub(crate) fn pack_metadata_event(item: &SaleItem, editor: &Addr, tags: &[String]) -> Event {
+    event("pack_metadata", item)
+        .add_attribute("editor", editor)
+        .add_attribute("tags", tags.join(","))
+}
+
+pub(crate) fn unpack_event(item: &SaleItem, owner: &Addr) -> Event {
+    event("unpack", item).add_attribute("owner", owner)
+}
+
+pub(crate) fn transfer_event(item: &SaleItem, from: &Addr, to: &Addr) -> Event {
+    event("transfer", item)
+        .add_attribute("from", from)
+        .add_attribute("to", to)
+}
+
+pub(crate) fn listing_event(item: &SaleItem, owner: &Addr, for_sale: bool) -> Event {
+    let ty = if for_sale { "list" } else { "delist" };
+    event(ty, item).add_attribute("owner", owner)
+}
+
+pub(crate) fn token_uri_event(token_id: &str, token_uri: &str) -> Event {
+    event("token_uri", &SaleItem::Nft { token_id: token_id.to_string() }).add_attribute("token_uri", token_uri)
+}
+
+pub(crate) fn reveal_conflict_event(token_id: &str, token_uri: &str) -> Event {
+    event("reveal_conflict", &SaleItem::Nft { token_id: token_id.to_string() }).add_attribute("token_uri", token_uri)
+}
+
+pub(crate) fn batch_mint_event(minter: &Addr, token_ids: &[String], owners: &[Addr]) -> Event {
+    Event::new("batch_mint")
+        .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+        .add_attribute("minter", minter)
+        .add_attribute("count", token_ids.len().to_string())
+        .add_attribute("token_ids", token_ids.join(","))
+        .add_attribute("owners", owners.iter().map(Addr::as_str).collect::<Vec<_>>().join(","))
+}
+
+pub(crate) fn enqueue_event(token_ids: &[String], hidden: bool) -> Event {
+    Event::new("enqueue")
+        .add_attribute("schema_version", EVENT_SCHEMA_VERSION)
+        .add_attribute("token_ids", token_ids.join(","))
+        .add_attribute("hidden", hidden.to_string())
+}
+
+pub(crate) fn price_change_event(item: &SaleItem, owner: &Addr, old_price: Uint128, new_price: Uint128) -> Event {
+    event("price_change", item)
+        .add_attribute("owner", owner)
+        .add_attribute("old_price", old_price)
+        .add_attribute("new_price", new_price)
+}
+
+pub(crate) fn royalty_update_event(item: &SaleItem, owner: &Addr, royalty_fee: Decimal, recipient: &Addr) -> Event {
+    event("royalty_update", item)
+        .add_attribute("owner", owner)
+        .add_attribute("royalty_fee", royalty_fee.to_string())
+        .add_attribute("recipient", recipient)
+}
+
+/// the `sale` event followed by a `royalty_paid` event per royalty owner
+pub(crate) fn sale_events(item: &SaleItem, sale_id: u64, settlement: &Settlement, buyer: &Addr) -> Vec<Event> {
+    let denom = settlement.price.info.to_string();
+    let mut events = vec![event("sale", item)
+        .add_attribute("sale_id", sale_id.to_string())
+        .add_attribute("seller", &settlement.seller)
+        .add_attribute("buyer", buyer)
+        .add_attribute("price", settlement.price.amount)
+        .add_attribute("denom", &denom)
+        .add_attribute("fee", settlement.fee)
+        .add_attribute("tax", settlement.tax)
+        .add_attribute("seller_amount", settlement.seller_amount)];
+    for (position, (recipient, amount)) in settlement.royalties.iter().enumerate() {
+        events.push(event("royalty_paid", item)
+            .add_attribute("recipient", recipient)
+            .add_attribute("position", position.to_string())
+            .add_attribute("amount", *amount)
+            .add_attribute("denom", &denom));
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{coins, Coin, Empty, OwnedDeps, Response};
+
+    use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, PackKind};
+    use crate::state::{Cw721Contract, PackMetadata};
+    use crate::Extension;
+
+    type Deps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+    fn execute(deps: &mut Deps, sender: &str, funds: &[Coin], msg: ExecuteMsg<Extension>) -> Response {
+        Cw721Contract::<Extension, Empty>::default()
+            .execute(deps.as_mut(), mock_env(), mock_info(sender, funds), msg)
+            .unwrap()
+    }
+
+    /// a contract with an NFT minted and packed by `minter`, listed as pack 1 at 1000uusd
+    fn packed() -> (Deps, Response, Response) {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name: "nftmx".to_string(),
+            symbol: "NFTMX".to_string(),
+            minter: "minter".to_string(),
+            max_packable_nft: None,
+            max_pack_item_count: None,
+            max_royalty_owner: None,
+            buy_sell_fee: None,
+            fee_collector: None,
+            pauser: None,
+            pack_metadata_editor: None,
+            // the mock querier has no treasury module
+            deduct_tax: Some(false),
+            sale_denom: None,
+            max_royalty_fee: None,
+        };
+        Cw721Contract::<Extension, Empty>::default()
+            .instantiate(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+            .unwrap();
+        let mint = execute(&mut deps, "minter", &[], ExecuteMsg::MintPackable(MintMsg {
+            token_id: "t1".to_string(),
+            owner: "minter".to_string(),
+            token_uri: "ipfs://t1".to_string(),
+            extension: None,
+            name: "first".to_string(),
+            price: Uint128::new(500),
+            royalty_fee: None,
+        }));
+        let pack = execute(&mut deps, "minter", &[], ExecuteMsg::PackNfts {
+            token_ids: vec!["t1".to_string()],
+            pack_name: "pack".to_string(),
+            price: Uint128::new(1000),
+            royalty_fee: Decimal::percent(5),
+            metadata: None,
+        });
+        (deps, mint, pack)
+    }
+
+    fn find<'a>(res: &'a Response, ty: &str) -> &'a Event {
+        res.events.iter().find(|event| event.ty == ty).unwrap_or_else(|| panic!("no {} event", ty))
+    }
+
+    fn attr<'a>(event: &'a Event, key: &str) -> &'a str {
+        event.attributes.iter().find(|attr| attr.key == key).map(|attr| attr.value.as_str())
+            .unwrap_or_else(|| panic!("no {} attribute on {}", key, event.ty))
+    }
+
+    fn assert_item(event: &Event, item_type: &str, item_id: &str) {
+        assert_eq!(attr(event, "schema_version"), EVENT_SCHEMA_VERSION);
+        assert_eq!(attr(event, "item_type"), item_type);
+        assert_eq!(attr(event, "item_id"), item_id);
+    }
+
+    #[test]
+    fn mint_and_pack() {
+        let (_, mint, pack) = packed();
+        let event = find(&mint, "mint");
+        assert_item(event, "nft", "t1");
+        assert_eq!(attr(event, "owner"), "minter");
+        assert_eq!(attr(event, "minter"), "minter");
+        assert_eq!(attr(event, "token_uri"), "ipfs://t1");
+        assert_eq!(attr(event, "price"), "500");
+
+        let event = find(&pack, "pack");
+        assert_item(event, "nft_pack", "1");
+        assert_eq!(attr(event, "owner"), "minter");
+        assert_eq!(attr(event, "pack_name"), "pack");
+        assert_eq!(attr(event, "price"), "1000");
+        assert_eq!(attr(event, "royalty_fee"), "0.05");
+        assert_eq!(attr(event, "contents"), "t1");
+    }
+
+    #[test]
+    fn rename_and_metadata() {
+        let (mut deps, _, _) = packed();
+        let res = execute(&mut deps, "minter", &[], ExecuteMsg::RenamePack {
+            kind: PackKind::Nft,
+            pack_id: 1,
+            name: "renamed".to_string(),
+        });
+        let event = find(&res, "rename");
+        assert_item(event, "nft_pack", "1");
+        assert_eq!(attr(event, "owner"), "minter");
+        assert_eq!(attr(event, "old_name"), "pack");
+        assert_eq!(attr(event, "name"), "renamed");
+
+        let metadata = PackMetadata { tags: vec!["art".to_string(), "rare".to_string()], ..PackMetadata::default() };
+        let res = execute(&mut deps, "minter", &[], ExecuteMsg::UpdateNftPackMetadata { pack_id: 1, metadata });
+        let event = find(&res, "pack_metadata");
+        assert_item(event, "nft_pack", "1");
+        assert_eq!(attr(event, "editor"), "minter");
+        assert_eq!(attr(event, "tags"), "art,rare");
+
+        execute(&mut deps, "minter", &[], ExecuteMsg::PackTokens {
+            pack_name: "tokens".to_string(),
+            token_address: "token".to_string(),
+            amount: Uint128::new(100),
+            price: Uint128::new(1000),
+            royalty_fee: Decimal::percent(5),
+            metadata: None,
+        });
+        let metadata = PackMetadata { tags: vec![" Art ".to_string()], ..PackMetadata::default() };
+        let res = execute(&mut deps, "minter", &[], ExecuteMsg::UpdateTokenPackMetadata { pack_id: 1, metadata });
+        let event = find(&res, "pack_metadata");
+        assert_item(event, "token_pack", "1");
+        assert_eq!(attr(event, "tags"), "art");
+    }
+
+    #[test]
+    fn sale() {
+        let (mut deps, _, _) = packed();
+        let res = execute(&mut deps, "buyer", &coins(1000, "uusd"), ExecuteMsg::BuyNftPack { pack_id: 1, royalty_fee: None });
+        let event = find(&res, "sale");
+        assert_item(event, "nft_pack", "1");
+        assert_eq!(attr(event, "seller"), "minter");
+        assert_eq!(attr(event, "buyer"), "buyer");
+        assert_eq!(attr(event, "price"), "1000");
+        assert_eq!(attr(event, "denom"), "uusd");
+        assert_eq!(attr(event, "tax"), "0");
+
+        // the packer is the only royalty owner
+        let paid: Vec<&Event> = res.events.iter().filter(|event| event.ty == "royalty_paid").collect();
+        assert_eq!(paid.len(), 1);
+        assert_eq!(attr(paid[0], "recipient"), "minter");
+        assert_eq!(attr(paid[0], "position"), "0");
+
+        let event = find(&res, "delist");
+        assert_item(event, "nft_pack", "1");
+        assert_eq!(attr(event, "owner"), "minter");
+
+        let event = find(&res, "transfer");
+        assert_item(event, "nft_pack", "1");
+        assert_eq!(attr(event, "from"), "minter");
+        assert_eq!(attr(event, "to"), "buyer");
+    }
+
+    #[test]
+    fn enqueue() {
+        let (mut deps, _, _) = packed();
+        let res = execute(&mut deps, "minter", &[], ExecuteMsg::EnqueueMints {
+            mints: vec![MintMsg {
+                token_id: "t2".to_string(),
+                owner: "minter".to_string(),
+                token_uri: "ipfs://t2".to_string(),
+                extension: None,
+                name: "second".to_string(),
+                price: Uint128::new(500),
+                royalty_fee: None,
+            }],
+        });
+        let event = find(&res, "enqueue");
+        assert_eq!(attr(event, "schema_version"), EVENT_SCHEMA_VERSION);
+        assert_eq!(attr(event, "token_ids"), "t2");
+        assert_eq!(attr(event, "hidden"), "false");
+    }
+
+    #[test]
+    fn batch_mint() {
+        let (mut deps, _, _) = packed();
+        let mint = |token_id: &str, owner: &str| MintMsg {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: format!("ipfs://{}", token_id),
+            extension: None,
+            name: token_id.to_string(),
+            price: Uint128::new(500),
+            royalty_fee: None,
+        };
+        let res = execute(&mut deps, "minter", &[], ExecuteMsg::BatchMintPackable {
+            mints: vec![mint("t2", "alice"), mint("t3", "bob")],
+        });
+        assert_eq!(res.events.len(), 1);
+        let event = find(&res, "batch_mint");
+        assert_eq!(attr(event, "minter"), "minter");
+        assert_eq!(attr(event, "count"), "2");
+        assert_eq!(attr(event, "token_ids"), "t2,t3");
+        assert_eq!(attr(event, "owners"), "alice,bob");
+    }
+
+    #[test]
+    fn royalty_update() {
+        let (mut deps, _, _) = packed();
+        let res = execute(&mut deps, "minter", &[], ExecuteMsg::UpdateRoyaltyFee {
+            kind: PackKind::Nft,
+            pack_id: 1,
+            royalty_fee: Decimal::percent(2),
+        });
+        let event = find(&res, "royalty_update");
+        assert_item(event, "nft_pack", "1");
+        assert_eq!(attr(event, "owner"), "minter");
+        assert_eq!(attr(event, "royalty_fee"), "0.02");
+        assert_eq!(attr(event, "recipient"), "minter");
+    }
+}
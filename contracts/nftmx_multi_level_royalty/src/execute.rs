@@ -1,24 +1,52 @@
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Decimal, Uint128, CosmosMsg, WasmMsg};
+use cosmwasm_std::{to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, QuerierWrapper, Response, StdError, StdResult, Storage, Decimal, Uint128, CosmosMsg, WasmMsg, Event};
 
 use cw2::set_contract_version;
+use cw20::Cw20ExecuteMsg;
 use cw721::{ContractInfoResponse, CustomMsg, Cw721Execute, Cw721ReceiveMsg, Expiration};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg};
+use crate::events::{
+    batch_mint_event, burn_event, enqueue_event, listing_event, mint_event, pack_event, pack_items_event,
+    pack_metadata_event, price_change_event, rename_event, reveal_conflict_event, royalty_update_event, sale_events, token_uri_event,
+    transfer_event, unpack_event
+};
+use crate::merkle::{allowlist_leaf, parse_hash, verify_proof};
+use crate::msg::{CreatorShareMsg, ExecuteMsg, InstantiateMsg, MerkleClaim, MintMsg, PackKind, SaleItem};
+use crate::settlement::{payout_msgs, NetOfTax, Settlement};
 use crate::state::{
     Approval, Cw721Contract, TokenInfo, Config, CONFIG, ALLPACKABLENFTS, PackableToken, TOKENURIEXISTS,
-    TOKENNAMEEXISTS, NFTPACKCOUNTER, PACKNAMEEXISTS, NftPack, ROYALTYFEES, ALLNFTPACKS, NFTPACKBALANCES,
-    TokenPack, ALLTOKENPACKS, TOKENPACKCOUNTER, TOKENPACKNAMEEXISTS, TOKENPACKBALANCES, TOKENROYALTYFEES
+    TOKENNAMEEXISTS, NFTPACKCOUNTER, PACKNAMEEXISTS, NftPack, ROYALTYFEES, all_nft_packs,
+    TokenPack, all_token_packs, TOKENPACKCOUNTER, TOKENPACKNAMEEXISTS, TOKENROYALTYFEES,
+    OwnershipProposal, OWNERSHIPPROPOSAL, PauseState, PAUSER, PAUSESTATE, MinterInfo, MINTERS,
+    MintPhase, MINTPHASECOUNTER, MINTPHASES, MINTPHASEALLOWLIST, MINTPHASEMINTED, CreatorShare, CREATORSHARES,
+    MINTQUEUEHEAD, MINTQUEUETAIL, MINTQUEUETOKENIDS, MINTQUEUEHIDDEN, HiddenDrop, HIDDENDROP, HIDDENTOKENS, REVEALCONFLICTS,
+    PackMetadata, PackMetadataEditor, PACKTAGS, TOKENPACKTAGS, name_key, default_sale_denom, max_royalty_fee_bound, MAX_BUY_SELL_FEE_PERCENT, default_deduct_tax,
+    royalty_maps, royalty_recipient, add_holdings, remove_holdings,
+    move_holdings, listed_price, update_listing, update_pack_count, set_packable_owner, LISTINGS,
+    MAXCHAINROYALTY, add_rates, chain_royalty, record_chain_royalty
 };
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+use cw_storage_plus::Bound;
+use sha2::{Digest, Sha256};
 use crate::asset::{Asset, AssetInfo};
 
 // version info for migration info
-const CONTRACT_NAME: &str = "crates.io:cw721-base";
-const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub(crate) const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
+pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// upper bound of the buy/sell fee
+
+const DEFAULT_REVEAL_LIMIT: u32 = 50;
+const MAX_REVEAL_LIMIT: u32 = 200;
+
+const MAX_PACK_TAGS: usize = 10;
+const MAX_PACK_TAG_LENGTH: usize = 32;
+const MAX_PACK_DESCRIPTION_LENGTH: usize = 1024;
+const MAX_PACK_URI_LENGTH: usize = 512;
+const MAX_PACK_ATTRIBUTES: usize = 32;
+const MAX_PACK_ATTRIBUTE_LENGTH: usize = 64;
 
 impl<'a, T, C> Cw721Contract<'a, T, C>
 where
@@ -31,7 +59,7 @@ where
         _env: Env,
         _info: MessageInfo,
         msg: InstantiateMsg,
-    ) -> StdResult<Response<C>> {
+    ) -> Result<Response<C>, ContractError> {
         set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
         let info = ContractInfoResponse {
@@ -41,16 +69,36 @@ where
         self.contract_info.save(deps.storage, &info)?;
         let minter = deps.api.addr_validate(&msg.minter)?;
         self.minter.save(deps.storage, &minter)?;
+        MINTERS.save(deps.storage, &minter, &MinterInfo { quota: None, minted: 0u64 })?;
+        let max_royalty_owner = msg.max_royalty_owner.unwrap_or(10u64);
+        let buy_sell_fee = msg.buy_sell_fee.unwrap_or_else(Decimal::zero);
         let con = Config {
             collection_name: msg.name,
             collection_name_symbol: msg.symbol,
-            max_packable_nft: 5000u64,
-            max_pack_item_count: 10u64,
-            max_royalyty_owner: 10u64,
-            buy_sell_fee: Decimal::one(),
-            contract_owner: deps.api.addr_validate(&msg.minter)?
+            max_packable_nft: msg.max_packable_nft.unwrap_or(5000u64),
+            max_pack_item_count: msg.max_pack_item_count.unwrap_or(10u64),
+            max_royalyty_owner: max_royalty_owner,
+            buy_sell_fee,
+            contract_owner: minter.clone(),
+            pack_metadata_editor: msg.pack_metadata_editor.unwrap_or_default(),
+            deduct_tax: msg.deduct_tax.unwrap_or_else(default_deduct_tax),
+            sale_denom: msg.sale_denom.unwrap_or_else(default_sale_denom),
+            max_royalty_fee: msg
+                .max_royalty_fee
+                .unwrap_or_else(|| max_royalty_fee_bound(buy_sell_fee, max_royalty_owner)),
+            fee_collector: deps.api.addr_validate(&msg.fee_collector.unwrap_or(msg.minter))?
         };
+        check_collection_limits(&con, 0u64)?;
+        check_sale_denom(&con.sale_denom)?;
+        check_buy_sell_fee(con.buy_sell_fee)?;
+        check_max_royalty_fee(&con)?;
         CONFIG.save(deps.storage, &con)?;
+        let pauser = match msg.pauser {
+            Some(pauser) => deps.api.addr_validate(&pauser)?,
+            None => minter,
+        };
+        PAUSER.save(deps.storage, &pauser)?;
+        PAUSESTATE.save(deps.storage, &PauseState::default())?;
         NFTPACKCOUNTER.save(deps.storage, &0u64)?;
         TOKENPACKCOUNTER.save(deps.storage, &0u64)?;
         Ok(Response::default())
@@ -63,21 +111,84 @@ where
         info: MessageInfo,
         msg: ExecuteMsg<T>,
     ) -> Result<Response<C>, ContractError> {
+        self.check_not_paused(deps.as_ref(), &msg)?;
         match msg {
             ExecuteMsg::MintPackable(msg) => self.mint_packable(deps, env, info, msg),
-            ExecuteMsg::PackNfts { token_ids, pack_name, price, royalty_fee } => 
-                self.pack_nfts(deps, env, info, token_ids, pack_name, price, royalty_fee),
+            ExecuteMsg::BatchMintPackable { mints } => self.batch_mint_packable(deps, env, info, mints),
+            ExecuteMsg::MintPack { pack_name, items, price, royalty_fee, metadata }
+                => self.mint_pack(deps, env, info, pack_name, items, price, royalty_fee, metadata),
+            ExecuteMsg::AddMinter { minter, quota } => self.add_minter(deps, env, info, minter, quota),
+            ExecuteMsg::RemoveMinter { minter } => self.remove_minter(deps, env, info, minter),
+            ExecuteMsg::RenounceMinter {} => self.renounce_minter(deps, env, info),
+            ExecuteMsg::EnqueueMints { mints } => self.enqueue_mints(deps, env, info, mints),
+            ExecuteMsg::AddMintPhase { start, end, price, per_wallet_limit, allowlist, merkle_root }
+                => self.add_mint_phase(deps, env, info, start, end, price, per_wallet_limit, allowlist, merkle_root),
+            ExecuteMsg::RemoveMintPhase { phase_id } => self.remove_mint_phase(deps, env, info, phase_id),
+            ExecuteMsg::SetCreatorShares { shares } => self.set_creator_shares(deps, env, info, shares),
+            ExecuteMsg::StartHiddenDrop { placeholder_uri, seed_commitment, drop_size }
+                => self.start_hidden_drop(deps, env, info, placeholder_uri, seed_commitment, drop_size),
+            ExecuteMsg::Reveal { base_uri, seed } => self.reveal(deps, env, info, base_uri, seed),
+            ExecuteMsg::RevealTokens { start_after, limit } => self.reveal_tokens(deps, env, info, start_after, limit),
+            ExecuteMsg::ResolveRevealConflict { token_id, token_uri }
+                => self.resolve_reveal_conflict(deps, env, info, token_id, token_uri),
+            ExecuteMsg::PublicMint { phase_id, claim } => self.public_mint(deps, env, info, phase_id, claim),
+            ExecuteMsg::PackNfts { token_ids, pack_name, price, royalty_fee, metadata } =>
+                self.pack_nfts(deps, env, info, token_ids, pack_name, price, royalty_fee, metadata),
+            ExecuteMsg::UpdateNftPackMetadata { pack_id, metadata }
+                => self.update_nft_pack_metadata(deps, env, info, pack_id, metadata),
+            ExecuteMsg::UpdateTokenPackMetadata { pack_id, metadata }
+                => self.update_token_pack_metadata(deps, env, info, pack_id, metadata),
             ExecuteMsg::UnpackNfts { pack_id } => self.unpack_nfts(deps, env, info, pack_id),
+            ExecuteMsg::RenamePack { kind, pack_id, name } => self.rename_pack(deps, env, info, kind, pack_id, name),
+            ExecuteMsg::UpdateRoyaltyFee { kind, pack_id, royalty_fee } => {
+                self.update_royalty_fee(deps, env, info, kind, pack_id, royalty_fee)
+            }
+            ExecuteMsg::RenounceRoyalty { kind, pack_id } => self.renounce_royalty(deps, env, info, kind, pack_id),
+            ExecuteMsg::SetRoyaltyRecipient { kind, pack_id, recipient } => {
+                self.set_royalty_recipient(deps, env, info, kind, pack_id, recipient)
+            }
+            ExecuteMsg::AddToNftPack { pack_id, token_ids } => self.add_to_nft_pack(deps, env, info, pack_id, token_ids),
+            ExecuteMsg::RemoveFromNftPack { pack_id, token_ids }
+                => self.remove_from_nft_pack(deps, env, info, pack_id, token_ids),
             ExecuteMsg::ApproveNftPack { to, pack_id } => self.approve_nft_pack(deps, env, info, to, pack_id),
             ExecuteMsg::TransferNftPack { from, to, pack_id }
                 => self.transfer_nft_pack(deps, env, info, from, to, pack_id),
-            ExecuteMsg::PackTokens { pack_name, token_address, amount, price, royalty_fee }
-                => self.pack_tokens(deps, env, info, pack_name, token_address, amount, price, royalty_fee),
+            ExecuteMsg::PackTokens { pack_name, token_address, amount, price, royalty_fee, metadata }
+                => self.pack_tokens(deps, env, info, pack_name, token_address, amount, price, royalty_fee, metadata),
             ExecuteMsg::UnpackTokens { pack_id } => self.unpack_tokens(deps, env, info, pack_id),
             ExecuteMsg::ApproveTokenPack { pack_id, to } => self.approve_token_pack(deps, env, info, pack_id, to),
             ExecuteMsg::TransferTokenPack { pack_id, from, to } => self.transfer_token_pack(deps, env, info, pack_id, from, to),
-            ExecuteMsg::BuyTokenPack { pack_id } => self.buy_token_pack(deps, env, info, pack_id),
-            ExecuteMsg::SetBuyCellFee { fee } => self.set_buy_cell_fee(deps, env, info, fee),
+            ExecuteMsg::BuyNftPack { pack_id, royalty_fee } => self.buy_nft_pack(deps, env, info, pack_id, royalty_fee),
+            ExecuteMsg::BuyTokenPack { pack_id, royalty_fee } => self.buy_token_pack(deps, env, info, pack_id, royalty_fee),
+            ExecuteMsg::UpdateConfig {
+                buy_sell_fee,
+                fee_collector,
+                max_packable_nft,
+                max_pack_item_count,
+                max_royalty_owner,
+                pack_metadata_editor,
+                deduct_tax,
+                sale_denom,
+                max_royalty_fee,
+            } => self.update_config(
+                deps,
+                env,
+                info,
+                buy_sell_fee,
+                fee_collector,
+                max_packable_nft,
+                max_pack_item_count,
+                max_royalty_owner,
+                pack_metadata_editor,
+                deduct_tax,
+                sale_denom,
+                max_royalty_fee,
+            ),
+            ExecuteMsg::ProposeNewOwner { owner, expires } => self.propose_new_owner(deps, env, info, owner, expires),
+            ExecuteMsg::AcceptOwnership {} => self.accept_ownership(deps, env, info),
+            ExecuteMsg::SetPauser { pauser } => self.set_pauser(deps, env, info, pauser),
+            ExecuteMsg::SetPauseState { minting, packing, trading, transfers }
+                => self.set_pause_state(deps, env, info, minting, packing, trading, transfers),
             ExecuteMsg::SetTokenPrice { token_id, price } => self.set_token_price(deps, env, info, token_id, price),
             ExecuteMsg::SetNftPackPrice { pack_id, price } => self.set_nft_pack_price(deps, env, info, pack_id, price),
             ExecuteMsg::SetTokenPackPrice { pack_id, price } => self.set_token_pack_price(deps, env, info, pack_id, price),
@@ -124,58 +235,584 @@ where
         info: MessageInfo,
         msg: MintMsg<T>,
     ) -> Result<Response<C>, ContractError> {
-        let minter = self.minter.load(deps.storage)?;
-        if info.sender != minter {
-            return Err(ContractError::Unauthorized {});
+        let mut minter = MINTERS.may_load(deps.storage, &info.sender)?
+            .ok_or(ContractError::Unauthorized {})?;
+        if let Some(quota) = minter.quota {
+            if minter.minted >= quota {
+                return Err(ContractError::MintQuotaExceeded {});
+            }
         }
-        let token_uri_exist = TOKENURIEXISTS.load(deps.storage, &msg.token_uri)?;
-        if token_uri_exist {
+        // the placeholder replaces the URI of hidden mints
+        if !hidden_drop_active(deps.storage)? && TOKENURIEXISTS.has(deps.storage, &msg.token_uri) {
             return Err(ContractError::ExistTokenUri {});
         }
-        let token_name_exist = TOKENNAMEEXISTS.load(deps.storage, &msg.name)?;
-        if token_name_exist {
+        if TOKENNAMEEXISTS.has(deps.storage, &name_key(&msg.name)) {
             return Err(ContractError::ExistTokenName {});
         }
-        // create the token
-        let token = TokenInfo {
-            owner: deps.api.addr_validate(&msg.owner)?,
-            approvals: vec![],
-            token_uri: msg.token_uri.clone(),
-            extension: msg.extension.clone()
+        if MINTQUEUETOKENIDS.has(deps.storage, &msg.token_id) {
+            return Err(ContractError::Claimed {});
+        }
+        let con = CONFIG.load(deps.storage)?;
+        check_royalty_fee(&con, msg.royalty_fee.unwrap_or_default())?;
+        if self.token_count(deps.storage)? + queued_mint_count(deps.storage)? >= con.max_packable_nft {
+            return Err(ContractError::PackableNftSupplyExceeded {});
+        }
+        let owner = deps.api.addr_validate(&msg.owner)?;
+        add_holdings(deps.storage, &owner, 1)?;
+        let event = self._mint_packable(deps.storage, &info.sender, owner, msg.clone(), false)?;
+        minter.minted += 1;
+        MINTERS.save(deps.storage, &info.sender, &minter)?;
+        Ok(Response::new()
+            .add_event(event)
+            .add_attribute("action", "mint")
+            .add_attribute("minter", info.sender)
+            .add_attribute("token_id", msg.token_id))
+    }
+
+    pub fn batch_mint_packable(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        mints: Vec<MintMsg<T>>,
+    ) -> Result<Response<C>, ContractError> {
+        let mut minter = MINTERS.may_load(deps.storage, &info.sender)?
+            .ok_or(ContractError::Unauthorized {})?;
+        self.check_batch_mint(deps.as_ref(), &minter, &mints)?;
+        let count = mints.len() as u64;
+        let owners = mints.iter()
+            .map(|m| deps.api.addr_validate(&m.owner))
+            .collect::<StdResult<Vec<Addr>>>()?;
+
+        let token_ids: Vec<String> = mints.iter().map(|m| m.token_id.clone()).collect();
+        let event = batch_mint_event(&info.sender, &token_ids, &owners);
+        // one `batch_mint` event stands in for the per token `mint` events
+        for (msg, owner) in mints.into_iter().zip(owners) {
+            add_holdings(deps.storage, &owner, 1)?;
+            self._mint_packable(deps.storage, &info.sender, owner, msg, false)?;
+        }
+        minter.minted += count;
+        MINTERS.save(deps.storage, &info.sender, &minter)?;
+        Ok(Response::new()
+            .add_event(event)
+            .add_attribute("action", "batch_mint")
+            .add_attribute("minter", info.sender)
+            .add_attribute("count", count.to_string())
+            .add_attribute("token_ids", token_ids.join(",")))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn mint_pack(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        pack_name: String,
+        items: Vec<MintMsg<T>>,
+        price: Uint128,
+        royalty_fee: Decimal,
+        metadata: Option<PackMetadata>,
+    ) -> Result<Response<C>, ContractError> {
+        let mut minter = MINTERS.may_load(deps.storage, &info.sender)?
+            .ok_or(ContractError::Unauthorized {})?;
+        self.check_batch_mint(deps.as_ref(), &minter, &items)?;
+        let token_ids: Vec<String> = items.iter().map(|m| m.token_id.clone()).collect();
+        check_new_nft_pack(deps.storage, &pack_name, royalty_fee, &token_ids)?;
+        let metadata = normalize_pack_metadata(metadata.unwrap_or_default())?;
+
+        // items are minted straight to this contract, the `owner` of each entry is ignored
+        let count = items.len() as u64;
+        let mut events = vec![];
+        for msg in items {
+            events.push(self._mint_packable(deps.storage, &info.sender, env.contract.address.clone(), msg, false)?);
+        }
+        minter.minted += count;
+        MINTERS.save(deps.storage, &info.sender, &minter)?;
+
+        let pack_count = create_nft_pack(deps.storage, &info.sender, &pack_name, token_ids.clone(), price, royalty_fee, metadata)?;
+        events.push(pack_event(
+            &SaleItem::NftPack { pack_id: pack_count }, &info.sender, &pack_name, price, royalty_fee, token_ids.join(",")
+        ));
+        Ok(Response::new()
+            .add_events(events)
+            .add_attribute("action", "mint_pack")
+            .add_attribute("minter", info.sender)
+            .add_attribute("pack_id", pack_count.to_string())
+            .add_attribute("pack_name", pack_name)
+            .add_attribute("token_ids", token_ids.join(","))
+        )
+    }
+
+    pub fn enqueue_mints(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        mints: Vec<MintMsg<T>>,
+    ) -> Result<Response<C>, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        // entries queued during an unrevealed drop take its next indices, their own URIs would give the metadata away
+        let mut drop = HIDDENDROP.may_load(deps.storage)?.filter(|d| d.base_uri.is_none());
+        self.check_mint_entries(deps.as_ref(), &mints, drop.is_none())?;
+        let count = mints.len();
+        if let Some(drop) = drop.as_mut() {
+            if drop.minted + drop.reserved + count as u64 > drop.drop_size {
+                return Err(ContractError::HiddenDropFull {});
+            }
+            drop.reserved += count as u64;
+            HIDDENDROP.save(deps.storage, drop)?;
+        }
+        let token_ids: Vec<String> = mints.iter().map(|msg| msg.token_id.clone()).collect();
+        let mut tail = MINTQUEUETAIL.may_load(deps.storage)?.unwrap_or_default();
+        for mut msg in mints {
+            // reserve the entry until it is minted
+            MINTQUEUETOKENIDS.save(deps.storage, &msg.token_id, &true)?;
+            if drop.is_some() {
+                msg.token_uri = String::new();
+                MINTQUEUEHIDDEN.save(deps.storage, tail, &true)?;
+            } else {
+                TOKENURIEXISTS.save(deps.storage, &msg.token_uri, &true)?;
+            }
+            TOKENNAMEEXISTS.save(deps.storage, &name_key(&msg.name), &true)?;
+            self.mint_queue.save(deps.storage, tail, &msg)?;
+            tail += 1;
+        }
+        MINTQUEUETAIL.save(deps.storage, &tail)?;
+        Ok(Response::new()
+            .add_event(enqueue_event(&token_ids, drop.is_some()))
+            .add_attribute("action", "enqueue_mints")
+            .add_attribute("count", count.to_string())
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_mint_phase(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        start: Expiration,
+        end: Expiration,
+        price: Asset,
+        per_wallet_limit: Option<u64>,
+        allowlist: Option<Vec<String>>,
+        merkle_root: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        // reject expired data as invalid
+        if end.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
+        }
+        if let AssetInfo::Token { contract_addr } = &price.info {
+            deps.api.addr_validate(contract_addr)?;
+        }
+        if let Some(root) = &merkle_root {
+            parse_hash(root)?;
+        }
+        let phase_id = MINTPHASECOUNTER.may_load(deps.storage)?.unwrap_or_default() + 1;
+        MINTPHASECOUNTER.save(deps.storage, &phase_id)?;
+        let phase = MintPhase {
+            start,
+            end,
+            price: price.clone(),
+            per_wallet_limit,
+            allowlist_only: allowlist.is_some(),
+            merkle_root,
         };
-        self.tokens
-            .update(deps.storage, &msg.token_id.clone(), |old| match old {
-                Some(_) => Err(ContractError::Claimed {}),
-                None => Ok(token),
-            })?;
-        self.increment_tokens(deps.storage)?;
+        MINTPHASES.save(deps.storage, phase_id, &phase)?;
+        for address in allowlist.unwrap_or_default() {
+            let addr = deps.api.addr_validate(&address)?;
+            MINTPHASEALLOWLIST.save(deps.storage, (phase_id, &addr), &true)?;
+        }
+        Ok(Response::new()
+            .add_attribute("action", "add_mint_phase")
+            .add_attribute("phase_id", phase_id.to_string())
+            .add_attribute("price", price.to_string())
+        )
+    }
 
-        let packable_token = PackableToken {
-            token_id: msg.token_id.clone(),
-            token_name: msg.name.clone(),
-            token_uri: msg.token_uri.clone(),
-            minted_by: info.sender.clone(),
-            current_owner: info.sender.clone(),
-            previous_owner: None,
-            price: msg.price.clone(),
-            number_of_transfers: Uint128::zero(),
-            for_sale: true
+    pub fn remove_mint_phase(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        phase_id: u64,
+    ) -> Result<Response<C>, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        if !MINTPHASES.has(deps.storage, phase_id) {
+            return Err(ContractError::MintPhaseNotActive {});
+        }
+        MINTPHASES.remove(deps.storage, phase_id);
+        // the allowlist and mint counts of the phase go with it
+        let allowlisted = MINTPHASEALLOWLIST
+            .prefix(phase_id)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for address in allowlisted {
+            MINTPHASEALLOWLIST.remove(deps.storage, (phase_id, &address));
+        }
+        let minters = MINTPHASEMINTED
+            .prefix(phase_id)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for address in minters {
+            MINTPHASEMINTED.remove(deps.storage, (phase_id, &address));
+        }
+        Ok(Response::new()
+            .add_attribute("action", "remove_mint_phase")
+            .add_attribute("phase_id", phase_id.to_string())
+        )
+    }
+
+    pub fn set_creator_shares(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        shares: Vec<CreatorShareMsg>,
+    ) -> Result<Response<C>, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        let mut total = Decimal::zero();
+        let mut creator_shares: Vec<CreatorShare> = vec![];
+        for share in shares {
+            total += share.share;
+            creator_shares.push(CreatorShare {
+                address: deps.api.addr_validate(&share.address)?,
+                share: share.share,
+            });
+        }
+        if total > Decimal::one() {
+            return Err(ContractError::InvalidCreatorShares {});
+        }
+        CREATORSHARES.save(deps.storage, &creator_shares)?;
+        Ok(Response::new()
+            .add_attribute("action", "set_creator_shares")
+            .add_attribute("total_share", total.to_string())
+        )
+    }
+
+    pub fn start_hidden_drop(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        placeholder_uri: String,
+        seed_commitment: String,
+        drop_size: u64,
+    ) -> Result<Response<C>, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        // the previous drop has to be revealed and written first
+        if HIDDENDROP.may_load(deps.storage)?.is_some() {
+            return Err(ContractError::HiddenDropActive {});
+        }
+        if drop_size == 0 {
+            return Err(ContractError::HiddenDropFull {});
+        }
+        let mut commitment = [0u8; 32];
+        hex::decode_to_slice(&seed_commitment, &mut commitment)
+            .map_err(|_| ContractError::InvalidSeedCommitment {})?;
+        let drop = HiddenDrop {
+            placeholder_uri: placeholder_uri.clone(),
+            seed_commitment,
+            drop_size,
+            minted: 0u64,
+            reserved: 0u64,
+            base_uri: None,
+            offset: None,
         };
-        ALLPACKABLENFTS
-            .update(deps.storage, &msg.token_id.clone(), |old| match old {
-                Some(_) => Err(ContractError::Claimed {}),
-                None => Ok(packable_token),
-            })?;
-        TOKENURIEXISTS.update(deps.storage, &msg.token_uri, |_| -> StdResult<_> {
-            Ok(true)
-        })?;
-        TOKENNAMEEXISTS.update(deps.storage, &msg.name, |_| -> StdResult<_> {
-            Ok(true)
+        HIDDENDROP.save(deps.storage, &drop)?;
+        Ok(Response::new()
+            .add_attribute("action", "start_hidden_drop")
+            .add_attribute("placeholder_uri", placeholder_uri)
+            .add_attribute("seed_commitment", drop.seed_commitment)
+            .add_attribute("drop_size", drop_size.to_string())
+        )
+    }
+
+    pub fn reveal(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        base_uri: String,
+        seed: String,
+    ) -> Result<Response<C>, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        let mut drop = HIDDENDROP.may_load(deps.storage)?
+            .filter(|d| d.base_uri.is_none())
+            .ok_or(ContractError::NoHiddenDrop {})?;
+        let mut hasher = Sha256::new();
+        hasher.update(seed.as_bytes());
+        hasher.update(base_uri.as_bytes());
+        if hex::encode(hasher.finalize()) != drop.seed_commitment.to_lowercase() {
+            return Err(ContractError::InvalidRevealSeed {});
+        }
+        // the seed was fixed before minting but the reveal block only exists once minting into the
+        // drop has closed, so the admin can't know which index gets which metadata while tokens are minted
+        let mut hasher = Sha256::new();
+        hasher.update(seed.as_bytes());
+        hasher.update(env.block.height.to_be_bytes());
+        hasher.update(env.block.time.nanos().to_be_bytes());
+        hasher.update(env.block.chain_id.as_bytes());
+        let hash = hasher.finalize();
+        let mut offset_bytes = [0u8; 8];
+        offset_bytes.copy_from_slice(&hash[..8]);
+        let offset = u64::from_be_bytes(offset_bytes) % drop.drop_size;
+
+        drop.base_uri = Some(base_uri.clone());
+        drop.offset = Some(offset);
+        HIDDENDROP.save(deps.storage, &drop)?;
+        Ok(Response::new()
+            .add_attribute("action", "reveal")
+            .add_attribute("base_uri", base_uri)
+            .add_attribute("offset", offset.to_string())
+        )
+    }
+
+    pub fn reveal_tokens(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Response<C>, ContractError> {
+        let drop = HIDDENDROP.may_load(deps.storage)?
+            .filter(|d| d.base_uri.is_some())
+            .ok_or(ContractError::NoHiddenDrop {})?;
+        let limit = limit.unwrap_or(DEFAULT_REVEAL_LIMIT).min(MAX_REVEAL_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+        let hidden = HIDDENTOKENS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<(String, u64)>>>()?;
+
+        let mut conflicts = vec![];
+        let mut events = vec![];
+        for (token_id, index) in hidden.iter() {
+            let token_uri = drop.revealed_uri(*index).unwrap_or_default();
+            // a taken URI can't block the drop, the token waits for `ResolveRevealConflict`
+            if TOKENURIEXISTS.has(deps.storage, &token_uri) {
+                events.push(reveal_conflict_event(token_id, &token_uri));
+                REVEALCONFLICTS.save(deps.storage, token_id, &token_uri)?;
+                conflicts.push(token_id.clone());
+            } else {
+                events.push(token_uri_event(token_id, &token_uri));
+                self.set_token_uri(deps.storage, token_id, token_uri)?;
+            }
+            HIDDENTOKENS.remove(deps.storage, token_id);
+        }
+        // every token is written, the next drop can start
+        if drop.reserved == 0 && HIDDENTOKENS.keys(deps.storage, None, None, Order::Ascending).next().is_none() {
+            HIDDENDROP.remove(deps.storage);
+        }
+        Ok(Response::new()
+            .add_events(events)
+            .add_attribute("action", "reveal_tokens")
+            .add_attribute("count", hidden.len().to_string())
+            .add_attribute("conflicts", conflicts.join(","))
+        )
+    }
+
+    pub fn resolve_reveal_conflict(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+        token_uri: String,
+    ) -> Result<Response<C>, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        if !REVEALCONFLICTS.has(deps.storage, &token_id) {
+            return Err(ContractError::NoRevealConflict {});
+        }
+        if TOKENURIEXISTS.has(deps.storage, &token_uri) {
+            return Err(ContractError::ExistTokenUri {});
+        }
+        self.set_token_uri(deps.storage, &token_id, token_uri.clone())?;
+        REVEALCONFLICTS.remove(deps.storage, &token_id);
+        Ok(Response::new()
+            .add_event(token_uri_event(&token_id, &token_uri))
+            .add_attribute("action", "resolve_reveal_conflict")
+            .add_attribute("token_id", token_id)
+            .add_attribute("token_uri", token_uri)
+        )
+    }
+
+    pub fn public_mint(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        phase_id: u64,
+        claim: Option<MerkleClaim>,
+    ) -> Result<Response<C>, ContractError> {
+        let phase = MINTPHASES.may_load(deps.storage, phase_id)?
+            .ok_or(ContractError::MintPhaseNotActive {})?;
+        if !phase.start.is_expired(&env.block) || phase.end.is_expired(&env.block) {
+            return Err(ContractError::MintPhaseNotActive {});
+        }
+        if phase.allowlist_only && !MINTPHASEALLOWLIST.has(deps.storage, (phase_id, &info.sender)) {
+            return Err(ContractError::NotAllowlisted {});
+        }
+        let minted = MINTPHASEMINTED.may_load(deps.storage, (phase_id, &info.sender))?.unwrap_or_default();
+        if let Some(limit) = phase.per_wallet_limit {
+            if minted >= limit {
+                return Err(ContractError::WalletMintLimitReached {});
+            }
+        }
+        if let Some(root) = &phase.merkle_root {
+            let claim = claim.ok_or(ContractError::InvalidMerkleProof {})?;
+            let proof = claim.proof.iter()
+                .map(|hash| parse_hash(hash))
+                .collect::<Result<Vec<_>, _>>()?;
+            let leaf = allowlist_leaf(info.sender.as_str(), claim.allowance);
+            if !verify_proof(&parse_hash(root)?, leaf, &proof) {
+                return Err(ContractError::InvalidMerkleProof {});
+            }
+            if minted >= claim.allowance {
+                return Err(ContractError::WalletMintLimitReached {});
+            }
+        }
+
+        // collect the payment
+        let mut messages: Vec<CosmosMsg<C>> = vec![];
+        let overpaid = phase.price.assert_sent_funds(&info)?;
+        let con = CONFIG.load(deps.storage)?;
+        messages.extend(refund_msg(&deps.querier, &con, &phase.price.info, overpaid, &info.sender)?);
+        if let AssetInfo::Token { contract_addr } = &phase.price.info {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract_addr.clone(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: phase.price.amount,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        // mint the next registered NFT to the sender
+        let head = MINTQUEUEHEAD.may_load(deps.storage)?.unwrap_or_default();
+        let msg = self.mint_queue.may_load(deps.storage, head)?
+            .ok_or(ContractError::MintQueueEmpty {})?;
+        self.mint_queue.remove(deps.storage, head);
+        MINTQUEUEHEAD.save(deps.storage, &(head + 1))?;
+        MINTQUEUETOKENIDS.remove(deps.storage, &msg.token_id);
+        let reserved = MINTQUEUEHIDDEN.has(deps.storage, head);
+        MINTQUEUEHIDDEN.remove(deps.storage, head);
+        let token_id = msg.token_id.clone();
+        add_holdings(deps.storage, &info.sender, 1)?;
+        // the collection stays the minter and royalty recipient, the buyer only owns the token
+        let event = self._mint_packable(deps.storage, &con.contract_owner, info.sender.clone(), msg, reserved)?;
+        MINTPHASEMINTED.save(deps.storage, (phase_id, &info.sender), &(minted + 1))?;
+
+        // split the proceeds, the contract owner gets what the creators don't
+        let mut net_of_tax = NetOfTax::new(&deps.querier, &con, &phase.price.info)?;
+        let mut remaining = phase.price.amount;
+        let mut payouts = vec![];
+        for creator in CREATORSHARES.may_load(deps.storage)?.unwrap_or_default() {
+            let amount = phase.price.amount * creator.share;
+            remaining = remaining.checked_sub(amount).map_err(StdError::from)?;
+            payouts.push((creator.address, net_of_tax.apply(amount)?));
+        }
+        payouts.push((con.contract_owner, net_of_tax.apply(remaining)?));
+        messages.extend(payout_msgs(&deps.querier, &phase.price.info, payouts)?);
+
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_event(event)
+            .add_attribute("action", "public_mint")
+            .add_attribute("phase_id", phase_id.to_string())
+            .add_attribute("minter", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("price", phase.price.to_string())
+        )
+    }
+
+    pub fn add_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        minter: String,
+        quota: Option<u64>,
+    ) -> Result<Response<C>, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        let minter_addr = deps.api.addr_validate(&minter)?;
+        MINTERS.update(deps.storage, &minter_addr, |old| -> StdResult<_> {
+            match old {
+                Some(v) => Ok(MinterInfo { quota, ..v }),
+                None => Ok(MinterInfo { quota, minted: 0u64 }),
+            }
         })?;
         Ok(Response::new()
-            .add_attribute("action", "mint")
+            .add_attribute("action", "add_minter")
+            .add_attribute("minter", minter)
+            .add_attribute("quota", quota.map_or("none".to_string(), |q| q.to_string()))
+        )
+    }
+
+    pub fn remove_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        minter: String,
+    ) -> Result<Response<C>, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        let minter_addr = deps.api.addr_validate(&minter)?;
+        if !MINTERS.has(deps.storage, &minter_addr) {
+            return Err(ContractError::MinterNotFound {});
+        }
+        MINTERS.remove(deps.storage, &minter_addr);
+        Ok(Response::new()
+            .add_attribute("action", "remove_minter")
+            .add_attribute("minter", minter)
+        )
+    }
+
+    pub fn renounce_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+    ) -> Result<Response<C>, ContractError> {
+        if !MINTERS.has(deps.storage, &info.sender) {
+            return Err(ContractError::MinterNotFound {});
+        }
+        MINTERS.remove(deps.storage, &info.sender);
+        Ok(Response::new()
+            .add_attribute("action", "renounce_minter")
             .add_attribute("minter", info.sender)
-            .add_attribute("token_id", msg.token_id))
+        )
     }
 
     pub fn burn_packable(
@@ -191,18 +828,29 @@ where
 
         // burn packable
         let packable_token = ALLPACKABLENFTS.load(deps.storage, &token_id)?;        
-        TOKENURIEXISTS.remove(deps.storage, &packable_token.token_uri);
-        TOKENNAMEEXISTS.remove(deps.storage, &packable_token.token_name);
+        if HIDDENTOKENS.has(deps.storage, &token_id) || REVEALCONFLICTS.has(deps.storage, &token_id) {
+            // the placeholder was never registered
+            HIDDENTOKENS.remove(deps.storage, &token_id);
+            REVEALCONFLICTS.remove(deps.storage, &token_id);
+        } else {
+            TOKENURIEXISTS.remove(deps.storage, &packable_token.token_uri);
+        }
+        TOKENNAMEEXISTS.remove(deps.storage, &name_key(&packable_token.token_name));
         ALLPACKABLENFTS.remove(deps.storage, &token_id);
 
         self.decrement_tokens(deps.storage)?;
+        if token.owner != env.contract.address {
+            remove_holdings(deps.storage, &token.owner, 1)?;
+        }
 
         Ok(Response::new()
+            .add_event(burn_event(&token_id, &token.owner))
             .add_attribute("action", "burn_packable")
             .add_attribute("sender", info.sender)
             .add_attribute("token_id", token_id))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn pack_nfts(
         &self,
         deps: DepsMut,
@@ -211,19 +859,16 @@ where
         token_ids: Vec<String>,
         pack_name: String,
         price: Uint128,
-        royalty_fee: Decimal
+        royalty_fee: Decimal,
+        metadata: Option<PackMetadata>,
     ) -> Result<Response<C>, ContractError> {
-        //increment NFT Pack counter
-        let pack_name_exist = PACKNAMEEXISTS.may_load(deps.storage, &pack_name)?;
-        if pack_name_exist != None {
-            return Err(ContractError::ExistPackName {});
-        }
+        check_new_nft_pack(deps.storage, &pack_name, royalty_fee, &token_ids)?;
+        let metadata = normalize_pack_metadata(metadata.unwrap_or_default())?;
         let mut pack_items: Vec<String> = vec![];
         for token_id in token_ids.clone() {
             let mut token = self.tokens.load(deps.storage, &token_id)?;
             self.check_can_send(deps.as_ref(), &env, &info, &token)?;
-            let packable_nft = ALLPACKABLENFTS.load(deps.storage, &token_id)?;
-            if packable_nft.current_owner != info.sender {
+            if token.owner != info.sender {
                 return Err(ContractError::NotNftOwner {});
             }
             pack_items.push(token_id.clone());
@@ -232,91 +877,340 @@ where
             token.owner = env.contract.address.clone();
             token.approvals = vec![];
             self.tokens.save(deps.storage, &token_id, &token)?;
+            set_packable_owner(deps.storage, &token_id, &token.owner)?;
         }
-        // increase pack count
-        let pack_count = NFTPACKCOUNTER.load(deps.storage)? + 1;
-        NFTPACKCOUNTER.save(deps.storage, &pack_count)?;
+        remove_holdings(deps.storage, &info.sender, pack_items.len() as u64)?;
+        let contents = pack_items.join(",");
+        let pack_count = create_nft_pack(deps.storage, &info.sender, &pack_name, pack_items, price, royalty_fee, metadata)?;
+        Ok(Response::new()
+            .add_event(pack_event(&SaleItem::NftPack { pack_id: pack_count }, &info.sender, &pack_name, price, royalty_fee, contents))
+            .add_attribute("action", "pack_nfts")
+            .add_attribute("pack_id", pack_count.to_string())
+            .add_attribute("pack_name", pack_name)
+        )
+    }
 
-        let nft_pack = NftPack {
-            pack_id: pack_count,
-            pack_name: pack_name.clone(),
-            item_count: token_ids.len(),
-            pack_items: pack_items,
-            minted_by: info.sender.clone(),
-            current_owner: info.sender.clone(),
-            previous_owner: None,
-            current_price: price,
-            previous_price: Uint128::zero(),
-            number_of_transfers: 0u64,
-            for_sale: true,
-            royalty_owners: vec![info.sender.clone()],
-            approvals: vec![]
+    pub fn update_nft_pack_metadata(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        pack_id: u64,
+        metadata: PackMetadata
+    ) -> Result<Response<C>, ContractError> {
+        let mut nft_pack = all_nft_packs().load(deps.storage, pack_id)?;
+        let con = CONFIG.load(deps.storage)?;
+        if !con.pack_metadata_editor.can_edit(&nft_pack.current_owner, &nft_pack.minted_by, &info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        let metadata = normalize_pack_metadata(metadata)?;
+        for tag in nft_pack.metadata.tags.iter() {
+            PACKTAGS.remove(deps.storage, (tag, pack_id));
+        }
+        for tag in metadata.tags.iter() {
+            PACKTAGS.save(deps.storage, (tag, pack_id), &true)?;
+        }
+        let event = pack_metadata_event(&SaleItem::NftPack { pack_id }, &info.sender, &metadata.tags);
+        nft_pack.metadata = metadata;
+        all_nft_packs().save(deps.storage, pack_id, &nft_pack)?;
+        Ok(Response::new()
+            .add_event(event)
+            .add_attribute("action", "update_nft_pack_metadata")
+            .add_attribute("sender", info.sender)
+            .add_attribute("pack_id", pack_id.to_string())
+        )
+    }
+
+    pub fn update_token_pack_metadata(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        pack_id: u64,
+        metadata: PackMetadata
+    ) -> Result<Response<C>, ContractError> {
+        let mut token_pack = all_token_packs().load(deps.storage, pack_id)?;
+        let con = CONFIG.load(deps.storage)?;
+        if !con.pack_metadata_editor.can_edit(&token_pack.current_owner, &token_pack.minted_by, &info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        let metadata = normalize_pack_metadata(metadata)?;
+        for tag in token_pack.metadata.tags.iter() {
+            TOKENPACKTAGS.remove(deps.storage, (tag, pack_id));
+        }
+        for tag in metadata.tags.iter() {
+            TOKENPACKTAGS.save(deps.storage, (tag, pack_id), &true)?;
+        }
+        let event = pack_metadata_event(&SaleItem::TokenPack { pack_id }, &info.sender, &metadata.tags);
+        token_pack.metadata = metadata;
+        all_token_packs().save(deps.storage, pack_id, &token_pack)?;
+        Ok(Response::new()
+            .add_event(event)
+            .add_attribute("action", "update_token_pack_metadata")
+            .add_attribute("sender", info.sender)
+            .add_attribute("pack_id", pack_id.to_string())
+        )
+    }
+
+    pub fn unpack_nfts(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        pack_id: u64
+    ) -> Result<Response<C>, ContractError> {
+        let nft_pack = all_nft_packs().load(deps.storage, pack_id)?;
+        if nft_pack.current_owner != info.sender {
+            return Err(ContractError::NotNftOwner {});
+        }
+        for pack_item in nft_pack.pack_items.clone() {
+            //TODO check nft token owner
+            let mut token = self.tokens.load(deps.storage, &pack_item)?;
+            if token.owner != env.contract.address {
+                return Err(ContractError::InvalidNftOwner {});
+            }
+            //transfter token to sender
+            token.owner = info.sender.clone();
+            token.approvals = vec![];
+            self.tokens.save(deps.storage, &pack_item, &token)?;
+            set_packable_owner(deps.storage, &pack_item, &token.owner)?;
+        }
+        PACKNAMEEXISTS.remove(deps.storage, &name_key(&nft_pack.pack_name));
+        for tag in nft_pack.metadata.tags.iter() {
+            PACKTAGS.remove(deps.storage, (tag, pack_id));
+        }
+        all_nft_packs().remove(deps.storage, pack_id)?;
+        add_holdings(deps.storage, &info.sender, nft_pack.pack_items.len() as u64)?;
+        remove_holdings(deps.storage, &info.sender, 1)?;
+        let delisted = delist(deps.storage, &SaleItem::NftPack { pack_id }, &info.sender)?;
+        update_pack_count(deps.storage, PackKind::Nft, false)?;
+        Ok(Response::new()
+            .add_event(unpack_event(&SaleItem::NftPack { pack_id }, &info.sender))
+            .add_events(delisted)
+            .add_attribute("action", "unpack_nfts")
+            .add_attribute("pack_id", pack_id.to_string())
+        )
+    }
+
+    pub fn rename_pack(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        kind: PackKind,
+        pack_id: u64,
+        name: String
+    ) -> Result<Response<C>, ContractError> {
+        check_pack_name(&name)?;
+        let old_name = match kind {
+            PackKind::Nft => {
+                let mut nft_pack = all_nft_packs().load(deps.storage, pack_id)?;
+                if nft_pack.current_owner != info.sender {
+                    return Err(ContractError::NotNftOwner {});
+                }
+                let old_name = std::mem::replace(&mut nft_pack.pack_name, name.clone());
+                all_nft_packs().save(deps.storage, pack_id, &nft_pack)?;
+                old_name
+            }
+            PackKind::Token => {
+                let mut token_pack = all_token_packs().load(deps.storage, pack_id)?;
+                if token_pack.current_owner != info.sender {
+                    return Err(ContractError::NotTokenPackOwner {});
+                }
+                let old_name = std::mem::replace(&mut token_pack.pack_name, name.clone());
+                all_token_packs().save(deps.storage, pack_id, &token_pack)?;
+                old_name
+            }
         };
-        // save all NftPack
-        ALLNFTPACKS.save(deps.storage, &pack_count.to_string(), &nft_pack)?;
-        //update pack name exists
-        PACKNAMEEXISTS.update(deps.storage, &pack_name, |_| -> StdResult<_> {
-            Ok(true)
-        })?;
-        //update nft pack balnace
-        NFTPACKBALANCES.update(deps.storage, &info.sender.clone().to_string(), |old| -> StdResult<_> {
-            match old {
-                Some(v) => Ok(v + 1),
-                None => Ok(1u64),
+        let registry = match kind {
+            PackKind::Nft => PACKNAMEEXISTS,
+            PackKind::Token => TOKENPACKNAMEEXISTS,
+        };
+        // a pack may change the case of its own name
+        if name_key(&name) != name_key(&old_name) {
+            if registry.has(deps.storage, &name_key(&name)) {
+                return Err(ContractError::ExistPackName {});
             }
-        })?;
+            registry.remove(deps.storage, &name_key(&old_name));
+            registry.save(deps.storage, &name_key(&name), &true)?;
+        }
+        Ok(Response::new()
+            .add_event(rename_event(&SaleItem::pack(kind, pack_id), &info.sender, &old_name, &name))
+            .add_attribute("action", "rename_pack")
+            .add_attribute("pack_id", pack_id.to_string())
+            .add_attribute("old_name", old_name)
+            .add_attribute("name", name)
+        )
+    }
 
-        ROYALTYFEES.save(deps.storage, (&pack_count.to_string(), &info.sender.clone().to_string()), &royalty_fee)?;
+    pub fn update_royalty_fee(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        kind: PackKind,
+        pack_id: u64,
+        royalty_fee: Decimal
+    ) -> Result<Response<C>, ContractError> {
+        let old_fee = load_royalty_fee(deps.storage, kind, pack_id, &info.sender)?;
+        // lowering keeps the rate within every bound it was checked against
+        if royalty_fee > old_fee {
+            return Err(ContractError::RoyaltyFeeIncrease {});
+        }
+        let (fees, _) = royalty_maps(kind);
+        fees.save(deps.storage, (pack_id, info.sender.as_str()), &royalty_fee)?;
+        let recipient = royalty_recipient(deps.storage, kind, pack_id, &info.sender)?;
         Ok(Response::new()
-            .add_attribute("action", "pack_nfts")
-            .add_attribute("pack_id", pack_count.to_string())
-            .add_attribute("pack_name", pack_name)
+            .add_event(royalty_update_event(&SaleItem::pack(kind, pack_id), &info.sender, royalty_fee, &recipient))
+            .add_attribute("action", "update_royalty_fee")
+            .add_attribute("pack_id", pack_id.to_string())
+            .add_attribute("royalty_fee", royalty_fee.to_string())
+        )
+    }
+
+    pub fn renounce_royalty(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        kind: PackKind,
+        pack_id: u64
+    ) -> Result<Response<C>, ContractError> {
+        load_royalty_fee(deps.storage, kind, pack_id, &info.sender)?;
+        let (fees, _) = royalty_maps(kind);
+        fees.save(deps.storage, (pack_id, info.sender.as_str()), &Decimal::zero())?;
+        let recipient = royalty_recipient(deps.storage, kind, pack_id, &info.sender)?;
+        Ok(Response::new()
+            .add_event(royalty_update_event(&SaleItem::pack(kind, pack_id), &info.sender, Decimal::zero(), &recipient))
+            .add_attribute("action", "renounce_royalty")
+            .add_attribute("pack_id", pack_id.to_string())
+        )
+    }
+
+    pub fn set_royalty_recipient(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        kind: PackKind,
+        pack_id: u64,
+        recipient: Option<String>
+    ) -> Result<Response<C>, ContractError> {
+        let royalty_fee = load_royalty_fee(deps.storage, kind, pack_id, &info.sender)?;
+        let (_, recipients) = royalty_maps(kind);
+        let recipient = match recipient {
+            Some(recipient) => {
+                let recipient = deps.api.addr_validate(&recipient)?;
+                recipients.save(deps.storage, (pack_id, info.sender.as_str()), &recipient)?;
+                recipient
+            }
+            None => {
+                recipients.remove(deps.storage, (pack_id, info.sender.as_str()));
+                info.sender.clone()
+            }
+        };
+        Ok(Response::new()
+            .add_event(royalty_update_event(&SaleItem::pack(kind, pack_id), &info.sender, royalty_fee, &recipient))
+            .add_attribute("action", "set_royalty_recipient")
+            .add_attribute("pack_id", pack_id.to_string())
+            .add_attribute("recipient", recipient)
+        )
+    }
+
+    pub fn add_to_nft_pack(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        pack_id: u64,
+        token_ids: Vec<String>
+    ) -> Result<Response<C>, ContractError> {
+        let mut nft_pack = all_nft_packs().load(deps.storage, pack_id)?;
+        if nft_pack.current_owner != info.sender {
+            return Err(ContractError::NotNftOwner {});
+        }
+        // a buyer gets the items listed when they looked
+        if nft_pack.for_sale {
+            return Err(ContractError::PackForSale {});
+        }
+        if token_ids.is_empty() {
+            return Err(ContractError::NoPackItems {});
+        }
+        let con = CONFIG.load(deps.storage)?;
+        if (nft_pack.pack_items.len() + token_ids.len()) as u64 > con.max_pack_item_count {
+            return Err(ContractError::PackItemCountExceeded {});
+        }
+        check_duplicate_token_ids(&token_ids)?;
+        for token_id in token_ids.clone() {
+            let mut token = self.tokens.load(deps.storage, &token_id)?;
+            self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+            if token.owner != info.sender {
+                return Err(ContractError::NotNftOwner {});
+            }
+            nft_pack.pack_items.push(token_id.clone());
+
+            //transfter token to this
+            token.owner = env.contract.address.clone();
+            token.approvals = vec![];
+            self.tokens.save(deps.storage, &token_id, &token)?;
+            set_packable_owner(deps.storage, &token_id, &token.owner)?;
+        }
+        nft_pack.item_count = nft_pack.pack_items.len();
+        all_nft_packs().save(deps.storage, pack_id, &nft_pack)?;
+        remove_holdings(deps.storage, &info.sender, token_ids.len() as u64)?;
+        Ok(Response::new()
+            .add_event(pack_items_event(pack_id, &info.sender, &token_ids, &[]))
+            .add_attribute("action", "add_to_nft_pack")
+            .add_attribute("pack_id", pack_id.to_string())
+            .add_attribute("token_ids", token_ids.join(","))
         )
     }
 
-    pub fn unpack_nfts(
+    pub fn remove_from_nft_pack(
         &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        pack_id: u64
+        pack_id: u64,
+        token_ids: Vec<String>
     ) -> Result<Response<C>, ContractError> {
-        let nft_pack_balances = NFTPACKBALANCES.load(deps.storage, &info.sender.to_string())?;
-        if nft_pack_balances < 1u64 {
-            return Err(ContractError::NoNftBalance {});
-        }
-        let nft_pack = ALLNFTPACKS.load(deps.storage, &pack_id.to_string())?;
+        let mut nft_pack = all_nft_packs().load(deps.storage, pack_id)?;
         if nft_pack.current_owner != info.sender {
             return Err(ContractError::NotNftOwner {});
         }
-        let empty = ROYALTYFEES.may_load(deps.storage, (&pack_id.to_string(), &info.sender.clone().to_string()))?;
-        if empty == None {
-            return Err(ContractError::NoNftPackRoyalty {});
+        if nft_pack.for_sale {
+            return Err(ContractError::PackForSale {});
         }
-        for pack_item in nft_pack.pack_items.clone() {
-            //TODO check nft token owner
-            let mut token = self.tokens.load(deps.storage, &pack_item)?;
+        if token_ids.is_empty() {
+            return Err(ContractError::NoPackItems {});
+        }
+        for token_id in token_ids.clone() {
+            let index = nft_pack.pack_items.iter().position(|x| *x == token_id)
+                .ok_or(ContractError::NftNotInPack {})?;
+            nft_pack.pack_items.remove(index);
+
+            let mut token = self.tokens.load(deps.storage, &token_id)?;
             if token.owner != env.contract.address {
                 return Err(ContractError::InvalidNftOwner {});
             }
             //transfter token to sender
             token.owner = info.sender.clone();
             token.approvals = vec![];
-            self.tokens.save(deps.storage, &pack_item, &token)?;
+            self.tokens.save(deps.storage, &token_id, &token)?;
+            set_packable_owner(deps.storage, &token_id, &token.owner)?;
         }
-        PACKNAMEEXISTS.update(deps.storage, &nft_pack.pack_name, |_| -> StdResult<_> {
-            Ok(false)
-        })?;
-        NFTPACKBALANCES.update(deps.storage, &info.sender.clone().to_string(), |old| -> StdResult<_> {
-            match old {
-                Some(v) => Ok(v - 1),
-                None => Ok(0u64),
-            }
-        })?;
-        ALLNFTPACKS.remove(deps.storage, &pack_id.to_string());
+        // an empty pack should be closed with unpack_nfts instead
+        if nft_pack.pack_items.is_empty() {
+            return Err(ContractError::EmptyNftPack {});
+        }
+        nft_pack.item_count = nft_pack.pack_items.len();
+        all_nft_packs().save(deps.storage, pack_id, &nft_pack)?;
+        add_holdings(deps.storage, &info.sender, token_ids.len() as u64)?;
         Ok(Response::new()
-            .add_attribute("action", "unpack_nfts")
+            .add_event(pack_items_event(pack_id, &info.sender, &[], &token_ids))
+            .add_attribute("action", "remove_from_nft_pack")
             .add_attribute("pack_id", pack_id.to_string())
+            .add_attribute("token_ids", token_ids.join(","))
         )
     }
 
@@ -328,12 +1222,12 @@ where
         to: String,
         pack_id: u64
     ) -> Result<Response<C>, ContractError> {
-        let mut nft_pack = ALLNFTPACKS.load(deps.storage, &pack_id.to_string())?;
+        let mut nft_pack = all_nft_packs().load(deps.storage, pack_id)?;
         if nft_pack.current_owner != info.sender {
             return Err(ContractError::NotNftOwner {});
         }
         nft_pack.approvals.push(deps.api.addr_validate(&to)?);
-        ALLNFTPACKS.save(deps.storage, &pack_id.to_string(), &nft_pack)?;
+        all_nft_packs().save(deps.storage, pack_id, &nft_pack)?;
         Ok(Response::new()
             .add_attribute("action", "approve_nft_pack")
             .add_attribute("to", to)
@@ -350,32 +1244,31 @@ where
         to: String,
         pack_id: u64
     ) -> Result<Response<C>, ContractError> {
-        let mut nft_pack = ALLNFTPACKS.load(deps.storage, &pack_id.to_string())?;
-        if nft_pack.current_owner.to_string() != from {
+        let mut nft_pack = all_nft_packs().load(deps.storage, pack_id)?;
+        if nft_pack.current_owner != from {
             return Err(ContractError::NotNftOwner {});
         }
-        if nft_pack.approvals.len() == 0 || nft_pack.approvals.iter().find(|&x| *x == env.contract.address.clone()) == None {
+        if !nft_pack.approvals.contains(&env.contract.address) {
             return Err(ContractError::NotNftApproved {});
         }
         nft_pack.previous_owner = Some(deps.api.addr_validate(&from)?);
         nft_pack.current_owner = deps.api.addr_validate(&to)?;
-        nft_pack.previous_price = nft_pack.current_price.clone();
-        nft_pack.number_of_transfers = nft_pack.number_of_transfers.clone() + 1;
-        NFTPACKBALANCES.update(deps.storage, &from.clone(), |old| -> StdResult<_> {
-            match old {
-                Some(v) => Ok(v - 1),
-                None => Ok(0u64),
-            }
-        })?;
-        NFTPACKBALANCES.update(deps.storage, &to.clone(), |old| -> StdResult<_> {
-            match old {
-                Some(v) => Ok(v + 1),
-                None => Ok(0u64),
-            }
-        })?;
+        let con = CONFIG.load(deps.storage)?;
+        join_royalty_chain(
+            deps.storage, &con, PackKind::Nft, pack_id, &mut nft_pack.royalty_owners, &nft_pack.current_owner, Decimal::zero()
+        )?;
+        nft_pack.previous_price = nft_pack.current_price;
+        nft_pack.number_of_transfers += 1;
         nft_pack.approvals = vec![];
-        ALLNFTPACKS.save(deps.storage, &pack_id.to_string(), &nft_pack)?;
+        all_nft_packs().save(deps.storage, pack_id, &nft_pack)?;
+        if let Some(previous_owner) = &nft_pack.previous_owner {
+            move_holdings(deps.storage, previous_owner, &nft_pack.current_owner, 1)?;
+        }
+        let event = transfer_event(
+            &SaleItem::NftPack { pack_id }, nft_pack.previous_owner.as_ref().unwrap_or(&nft_pack.current_owner), &nft_pack.current_owner
+        );
         Ok(Response::new()
+            .add_event(event)
             .add_attribute("action", "transfer_nft_pack")
             .add_attribute("from", from)
             .add_attribute("to", to)
@@ -386,51 +1279,55 @@ where
     pub fn buy_nft_pack(
         &self,
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
-        pack_id: u64
+        pack_id: u64,
+        royalty_fee: Option<Decimal>,
     ) -> Result<Response<C>, ContractError> {
-        let nft_pack = ALLNFTPACKS.load(deps.storage, &pack_id.to_string())?;
-        if nft_pack.current_owner != info.sender {
-            return Err(ContractError::NotNftOwner {});
+        let mut nft_pack = all_nft_packs().load(deps.storage, pack_id)?;
+        if nft_pack.current_owner == info.sender {
+            return Err(ContractError::CannotBuyOwnPack {});
         }
-        if info.funds.len() < 1 || info.funds[0].amount < nft_pack.current_price  {
-            return Err(ContractError::InsufficientFunds {});
+        if !nft_pack.for_sale {
+            return Err(ContractError::NotForSale {});
         }
-
         let con = CONFIG.load(deps.storage)?;
-        let mut fee = info.funds[0].amount * con.buy_sell_fee;
-        let mut messages: Vec<CosmosMsg> = vec![];
-        let fee_asset = Asset {
-            info: AssetInfo::NativeToken{ denom: "uusd".to_string()},
-            amount: fee
-        };
-        messages.push(fee_asset.into_msg(&deps.querier, con.contract_owner.clone())?);
-        if nft_pack.current_price > nft_pack.previous_price {
-            for owner in nft_pack.royalty_owners {
-                let royalty_fee = ROYALTYFEES.load(deps.storage, (&pack_id.to_string(), &owner.to_string()))?;
-                let royalty = (nft_pack.current_price - nft_pack.previous_price) * royalty_fee;
-                let royalty_asset = Asset {
-                    info: AssetInfo::NativeToken{ denom: "uusd".to_string()},
-                    amount: royalty
-                };
-                messages.push(royalty_asset.into_msg(&deps.querier, owner.clone())?);
-                fee = fee + royalty;
-            }
-        }
-        let current_owner_fee_asset = Asset {
-            info: AssetInfo::NativeToken{ denom: "uusd".to_string()},
-            amount: info.funds[0].amount - fee
-        };
-        messages.push(current_owner_fee_asset.into_msg(&deps.querier, info.sender.clone())?);
-
-        //TODO transfer nft pack to sender
+        let item = SaleItem::NftPack { pack_id };
+        // charged in the denom it was listed in
+        let price = LISTINGS.load(deps.storage, &item.key())?;
+        let overpaid = price.assert_sent_funds(&info)?;
+        let refund = refund_msg(&deps.querier, &con, &price.info, overpaid, &info.sender)?;
+        let settlement = Settlement::new(deps.as_ref(), &item, price)?;
+        let sale_id = settlement.record(deps.storage, &env.block, &item, &info.sender)?;
+        let events = sale_events(&item, sale_id, &settlement, &info.sender);
+        let mut messages: Vec<CosmosMsg<C>> = settlement.into_msgs(&deps.querier)?;
+        messages.extend(refund);
+
+        // the pack changes hands at the price paid and leaves the market until its new owner lists it
+        let seller = nft_pack.current_owner.clone();
+        nft_pack.previous_owner = Some(seller.clone());
+        nft_pack.current_owner = info.sender.clone();
+        join_royalty_chain(
+            deps.storage, &con, PackKind::Nft, pack_id, &mut nft_pack.royalty_owners, &info.sender, royalty_fee.unwrap_or_default()
+        )?;
+        nft_pack.previous_price = nft_pack.current_price;
+        nft_pack.number_of_transfers += 1;
+        nft_pack.for_sale = false;
+        nft_pack.approvals = vec![];
+        all_nft_packs().save(deps.storage, pack_id, &nft_pack)?;
+        move_holdings(deps.storage, &seller, &info.sender, 1)?;
+        let delisted = delist(deps.storage, &item, &seller)?;
         Ok(Response::new()
+            .add_messages(messages)
+            .add_events(events)
+            .add_events(delisted)
+            .add_event(transfer_event(&item, &seller, &info.sender))
             .add_attribute("action", "buy_nft_pack")
             .add_attribute("pack_id", pack_id.to_string())
         )
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn pack_tokens(
         &self,
         deps: DepsMut,
@@ -440,12 +1337,16 @@ where
         token_address: String,
         amount: Uint128,
         price: Uint128,
-        royalty_fee: Decimal
+        royalty_fee: Decimal,
+        metadata: Option<PackMetadata>,
     ) -> Result<Response<C>, ContractError> {
-        let pack_name_exist = TOKENPACKNAMEEXISTS.may_load(deps.storage, &pack_name)?;
-        if pack_name_exist != None {
+        check_pack_name(&pack_name)?;
+        let metadata = normalize_pack_metadata(metadata.unwrap_or_default())?;
+        if TOKENPACKNAMEEXISTS.has(deps.storage, &name_key(&pack_name)) {
             return Err(ContractError::ExistPackName {});
         }
+        let con = CONFIG.load(deps.storage)?;
+        check_royalty_fee(&con, royalty_fee)?;
         // TODO - transfer token to this smart contract - should be performed by user
         let pack_count = TOKENPACKCOUNTER.load(deps.storage)? + 1;
         TOKENPACKCOUNTER.save(deps.storage, &pack_count)?;
@@ -464,24 +1365,25 @@ where
             for_sale: true,
             royalty_owners: vec![info.sender.clone()],
             approvals: vec![],
+            metadata,
         };
         
         // update all TokenPack
-        ALLTOKENPACKS.save(deps.storage, &pack_count.to_string(), &token_pack)?;
+        all_token_packs().save(deps.storage, pack_count, &token_pack)?;
+        for tag in token_pack.metadata.tags.iter() {
+            TOKENPACKTAGS.save(deps.storage, (tag, pack_count), &true)?;
+        }
         //update pack name exists
-        TOKENPACKNAMEEXISTS.update(deps.storage, &pack_name, |_| -> StdResult<_> {
-            Ok(true)
-        })?;
-        //update token packabale balance
-        TOKENPACKBALANCES.update(deps.storage, &info.sender.clone().to_string(), |old| -> StdResult<_> {
-            match old {
-                Some(v) => Ok(v + 1),
-                None => Ok(1u64),
-            }
-        })?;
-        TOKENROYALTYFEES.save(deps.storage, (&pack_count.to_string(), &info.sender.clone().to_string()), &royalty_fee)?;
-
+        TOKENPACKNAMEEXISTS.save(deps.storage, &name_key(&pack_name), &true)?;
+        TOKENROYALTYFEES.save(deps.storage, (pack_count, info.sender.as_str()), &royalty_fee)?;
+        record_chain_royalty(deps.storage, royalty_fee)?;
+        add_holdings(deps.storage, &info.sender, 1)?;
+        update_listing(deps.storage, &SaleItem::TokenPack { pack_id: pack_count }, Some(price))?;
+        update_pack_count(deps.storage, PackKind::Token, true)?;
+
+        let contents = format!("{}{}", amount, token_pack.token_address);
         Ok(Response::new()
+            .add_event(pack_event(&SaleItem::TokenPack { pack_id: pack_count }, &info.sender, &pack_name, price, royalty_fee, contents))
             .add_attribute("action", "pack_tokens")
             .add_attribute("pack_name", pack_name)
             .add_attribute("token_address", token_address)
@@ -498,37 +1400,30 @@ where
         info: MessageInfo,
         pack_id: u64,
     ) -> Result<Response<C>, ContractError> {
-        let token_pack_balances = TOKENPACKBALANCES.load(deps.storage, &info.sender.to_string())?;
-        if token_pack_balances < 1u64 {
-            return Err(ContractError::NoTokenBalance {});
-        }
-        let token_pack = ALLTOKENPACKS.load(deps.storage, &pack_id.to_string())?;
+        let token_pack = all_token_packs().load(deps.storage, pack_id)?;
         if token_pack.current_owner != info.sender {
             return Err(ContractError::NotTokenPackOwner {});
         }
-        let empty = TOKENROYALTYFEES.may_load(deps.storage, (&pack_id.to_string(), &info.sender.clone().to_string()))?;
-        if empty == None {
-            return Err(ContractError::NoTokenPackRoyalty {});
-        }
 
         //TODO check token balance in this address
-        let mut messages: Vec<CosmosMsg> = vec![];
+        let mut messages: Vec<CosmosMsg<C>> = vec![];
         let asset = Asset {
             info: AssetInfo::Token {contract_addr: token_pack.token_address.clone().to_string()},
             amount: token_pack.token_amount
         };
         messages.push(asset.into_msg(&deps.querier, info.sender.clone())?);
-        TOKENPACKNAMEEXISTS.update(deps.storage, &token_pack.pack_name, |_| -> StdResult<_> {
-            Ok(false)
-        })?;
-        TOKENPACKBALANCES.update(deps.storage, &info.sender.clone().to_string(), |old| -> StdResult<_> {
-            match old {
-                Some(v) => Ok(v - 1),
-                None => Ok(0u64),
-            }
-        })?;
-        ALLTOKENPACKS.remove(deps.storage, &pack_id.to_string());
+        TOKENPACKNAMEEXISTS.remove(deps.storage, &name_key(&token_pack.pack_name));
+        for tag in token_pack.metadata.tags.iter() {
+            TOKENPACKTAGS.remove(deps.storage, (tag, pack_id));
+        }
+        all_token_packs().remove(deps.storage, pack_id)?;
+        remove_holdings(deps.storage, &info.sender, 1)?;
+        let delisted = delist(deps.storage, &SaleItem::TokenPack { pack_id }, &info.sender)?;
+        update_pack_count(deps.storage, PackKind::Token, false)?;
         Ok(Response::new()
+            .add_messages(messages)
+            .add_event(unpack_event(&SaleItem::TokenPack { pack_id }, &info.sender))
+            .add_events(delisted)
             .add_attribute("action", "unpack_tokens")
             .add_attribute("pack_id", pack_id.to_string())
         )
@@ -542,12 +1437,12 @@ where
         pack_id: u64,
         to: String
     ) -> Result<Response<C>, ContractError> {
-        let mut token_pack = ALLTOKENPACKS.load(deps.storage, &pack_id.to_string())?;
+        let mut token_pack = all_token_packs().load(deps.storage, pack_id)?;
         if token_pack.current_owner != info.sender {
             return Err(ContractError::NotTokenPackOwner {});
         }
         token_pack.approvals.push(deps.api.addr_validate(&to)?);
-        ALLTOKENPACKS.save(deps.storage, &pack_id.to_string(), &token_pack)?;
+        all_token_packs().save(deps.storage, pack_id, &token_pack)?;
         Ok(Response::new()
             .add_attribute("action", "approve_token_pack")
             .add_attribute("pack_id", pack_id.to_string())
@@ -564,32 +1459,31 @@ where
         from: String,
         to: String
     ) -> Result<Response<C>, ContractError> {
-        let mut token_pack = ALLTOKENPACKS.load(deps.storage, &pack_id.to_string())?;
-        if token_pack.current_owner.to_string() != from {
+        let mut token_pack = all_token_packs().load(deps.storage, pack_id)?;
+        if token_pack.current_owner != from {
             return Err(ContractError::NotTokenPackOwner {});
         }
-        if token_pack.approvals.len() == 0 || token_pack.approvals.iter().find(|&x| *x == env.contract.address.clone()) == None {
+        if !token_pack.approvals.contains(&env.contract.address) {
             return Err(ContractError::NotTokenApproved {});
         }
         token_pack.previous_owner = Some(token_pack.current_owner.clone());
         token_pack.current_owner = deps.api.addr_validate(&to)?;
-        token_pack.previous_price = token_pack.current_price.clone();
-        token_pack.number_of_transfers = token_pack.number_of_transfers + 1;
-        TOKENPACKBALANCES.update(deps.storage, &from.clone(), |old| -> StdResult<_> {
-            match old {
-                Some(v) => Ok(v - 1),
-                None => Ok(0u64),
-            }
-        })?;
-        TOKENPACKBALANCES.update(deps.storage, &to.clone(), |old| -> StdResult<_> {
-            match old {
-                Some(v) => Ok(v + 1),
-                None => Ok(0u64),
-            }
-        })?;
+        let con = CONFIG.load(deps.storage)?;
+        join_royalty_chain(
+            deps.storage, &con, PackKind::Token, pack_id, &mut token_pack.royalty_owners, &token_pack.current_owner, Decimal::zero()
+        )?;
+        token_pack.previous_price = token_pack.current_price;
+        token_pack.number_of_transfers += 1;
         token_pack.approvals = vec![];
-        ALLTOKENPACKS.save(deps.storage, &pack_id.to_string(), &token_pack)?;
+        all_token_packs().save(deps.storage, pack_id, &token_pack)?;
+        if let Some(previous_owner) = &token_pack.previous_owner {
+            move_holdings(deps.storage, previous_owner, &token_pack.current_owner, 1)?;
+        }
+        let event = transfer_event(
+            &SaleItem::TokenPack { pack_id }, token_pack.previous_owner.as_ref().unwrap_or(&token_pack.current_owner), &token_pack.current_owner
+        );
         Ok(Response::new()
+            .add_event(event)
             .add_attribute("action", "transfer_token_pack")
             .add_attribute("pack_id", pack_id.to_string())
             .add_attribute("from", from)
@@ -600,66 +1494,223 @@ where
     pub fn buy_token_pack(
         &self,
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
         pack_id: u64,
+        royalty_fee: Option<Decimal>,
     ) -> Result<Response<C>, ContractError> {
-        let token_pack = ALLTOKENPACKS.load(deps.storage, &pack_id.to_string())?;
-        if token_pack.current_owner != info.sender {
-            return Err(ContractError::NotTokenPackOwner {});
+        let mut token_pack = all_token_packs().load(deps.storage, pack_id)?;
+        if token_pack.current_owner == info.sender {
+            return Err(ContractError::CannotBuyOwnPack {});
         }
-        if info.funds.len() < 1 || info.funds[0].amount < token_pack.current_price  {
-            return Err(ContractError::InsufficientFunds {});
+        if !token_pack.for_sale {
+            return Err(ContractError::NotForSale {});
         }
         let con = CONFIG.load(deps.storage)?;
-        let mut fee = info.funds[0].amount * con.buy_sell_fee;
-        let mut messages: Vec<CosmosMsg> = vec![];
-        let fee_asset = Asset {
-            info: AssetInfo::NativeToken{ denom: "uusd".to_string()},
-            amount: fee
-        };
-        messages.push(fee_asset.into_msg(&deps.querier, con.contract_owner.clone())?);
-        if token_pack.current_price > token_pack.previous_price {
-            for owner in token_pack.royalty_owners {
-                let royalty_fee = TOKENROYALTYFEES.load(deps.storage, (&pack_id.to_string(), &owner.to_string()))?;
-                let royalty = (token_pack.current_price - token_pack.previous_price) * royalty_fee;
-                let royalty_asset = Asset {
-                    info: AssetInfo::NativeToken{ denom: "uusd".to_string()},
-                    amount: royalty
-                };
-                messages.push(royalty_asset.into_msg(&deps.querier, owner.clone())?);
-                fee = fee + royalty;
-            }
+        let item = SaleItem::TokenPack { pack_id };
+        let price = LISTINGS.load(deps.storage, &item.key())?;
+        let overpaid = price.assert_sent_funds(&info)?;
+        let refund = refund_msg(&deps.querier, &con, &price.info, overpaid, &info.sender)?;
+        let settlement = Settlement::new(deps.as_ref(), &item, price)?;
+        let sale_id = settlement.record(deps.storage, &env.block, &item, &info.sender)?;
+        let events = sale_events(&item, sale_id, &settlement, &info.sender);
+        let mut messages: Vec<CosmosMsg<C>> = settlement.into_msgs(&deps.querier)?;
+        messages.extend(refund);
+
+        let seller = token_pack.current_owner.clone();
+        token_pack.previous_owner = Some(seller.clone());
+        token_pack.current_owner = info.sender.clone();
+        join_royalty_chain(
+            deps.storage, &con, PackKind::Token, pack_id, &mut token_pack.royalty_owners, &info.sender, royalty_fee.unwrap_or_default()
+        )?;
+        token_pack.previous_price = token_pack.current_price;
+        token_pack.number_of_transfers += 1;
+        token_pack.for_sale = false;
+        token_pack.approvals = vec![];
+        all_token_packs().save(deps.storage, pack_id, &token_pack)?;
+        move_holdings(deps.storage, &seller, &info.sender, 1)?;
+        let delisted = delist(deps.storage, &item, &seller)?;
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_events(events)
+            .add_events(delisted)
+            .add_event(transfer_event(&item, &seller, &info.sender))
+            .add_attribute("action", "buy_token_pack")
+            .add_attribute("pack_id", pack_id.to_string())
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_config(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        buy_sell_fee: Option<Decimal>,
+        fee_collector: Option<String>,
+        max_packable_nft: Option<u64>,
+        max_pack_item_count: Option<u64>,
+        max_royalty_owner: Option<u64>,
+        pack_metadata_editor: Option<PackMetadataEditor>,
+        deduct_tax: Option<bool>,
+        sale_denom: Option<String>,
+        max_royalty_fee: Option<Decimal>,
+    ) -> Result<Response<C>, ContractError> {
+        let mut con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        if let Some(v) = buy_sell_fee {
+            con.buy_sell_fee = v;
+        }
+        if let Some(v) = fee_collector {
+            con.fee_collector = deps.api.addr_validate(&v)?;
+        }
+        if let Some(v) = max_packable_nft {
+            con.max_packable_nft = v;
+        }
+        if let Some(v) = max_pack_item_count {
+            con.max_pack_item_count = v;
+        }
+        if let Some(v) = max_royalty_owner {
+            con.max_royalyty_owner = v;
+        }
+        if let Some(v) = pack_metadata_editor {
+            con.pack_metadata_editor = v;
+        }
+        if let Some(v) = deduct_tax {
+            con.deduct_tax = v;
+        }
+        if let Some(v) = sale_denom {
+            con.sale_denom = v;
+        }
+        if let Some(v) = max_royalty_fee {
+            con.max_royalty_fee = v;
+        }
+        check_sale_denom(&con.sale_denom)?;
+        check_buy_sell_fee(con.buy_sell_fee)?;
+        check_max_royalty_fee(&con)?;
+        // rates already on a chain stay, so the fee has to leave room for the highest chain
+        let total = add_rates(con.buy_sell_fee, MAXCHAINROYALTY.may_load(deps.storage)?.unwrap_or_default())?;
+        if total > Decimal::one() {
+            return Err(ContractError::RoyaltyChainTooHigh { total });
+        }
+        // queued mints are already promised a slot in the supply
+        check_collection_limits(&con, self.token_count(deps.storage)? + queued_mint_count(deps.storage)?)?;
+        CONFIG.save(deps.storage, &con)?;
+        Ok(Response::new()
+            .add_attribute("action", "update_config")
+            .add_attribute("buy_sell_fee", con.buy_sell_fee.to_string())
+            .add_attribute("fee_collector", con.fee_collector)
+            .add_attribute("max_packable_nft", con.max_packable_nft.to_string())
+            .add_attribute("max_pack_item_count", con.max_pack_item_count.to_string())
+            .add_attribute("max_royalty_owner", con.max_royalyty_owner.to_string())
+            .add_attribute("deduct_tax", con.deduct_tax.to_string())
+            .add_attribute("sale_denom", con.sale_denom)
+            .add_attribute("max_royalty_fee", con.max_royalty_fee.to_string())
+        )
+    }
+
+    pub fn propose_new_owner(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        owner: String,
+        expires: Option<Expiration>,
+    ) -> Result<Response<C>, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        if con.contract_owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        // reject expired data as invalid
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::Expired {});
         }
-        let current_owner_fee_asset = Asset {
-            info: AssetInfo::NativeToken{ denom: "uusd".to_string()},
-            amount: info.funds[0].amount - fee
+        let proposal = OwnershipProposal {
+            owner: deps.api.addr_validate(&owner)?,
+            expires,
         };
-        messages.push(current_owner_fee_asset.into_msg(&deps.querier, info.sender.clone())?);
-        //TODO transfer token pack
+        OWNERSHIPPROPOSAL.save(deps.storage, &proposal)?;
         Ok(Response::new()
-            .add_attribute("action", "transfer_token_pack")
-            .add_attribute("pack_id", pack_id.to_string())
+            .add_attribute("action", "propose_new_owner")
+            .add_attribute("owner", owner)
+            .add_attribute("expires", expires.to_string())
+        )
+    }
+
+    pub fn accept_ownership(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response<C>, ContractError> {
+        let proposal = OWNERSHIPPROPOSAL.may_load(deps.storage)?
+            .ok_or(ContractError::NoOwnershipProposal {})?;
+        if proposal.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        if proposal.expires.is_expired(&env.block) {
+            return Err(ContractError::OwnershipProposalExpired {});
+        }
+        let mut con = CONFIG.load(deps.storage)?;
+        let previous_owner = con.contract_owner;
+        con.contract_owner = proposal.owner;
+        CONFIG.save(deps.storage, &con)?;
+        OWNERSHIPPROPOSAL.remove(deps.storage);
+        Ok(Response::new()
+            .add_attribute("action", "accept_ownership")
+            .add_attribute("previous_owner", previous_owner)
+            .add_attribute("owner", info.sender)
         )
     }
 
-    pub fn set_buy_cell_fee(
+    pub fn set_pauser(
         &self,
         deps: DepsMut,
         _env: Env,
         info: MessageInfo,
-        fee: Decimal,
+        pauser: String,
     ) -> Result<Response<C>, ContractError> {
         let con = CONFIG.load(deps.storage)?;
         if con.contract_owner != info.sender {
             return Err(ContractError::Unauthorized {});
         }
-        CONFIG.update(deps.storage, |mut c| -> StdResult<_> {
-            c.buy_sell_fee = fee;
-            Ok(c)
-        })?;
+        PAUSER.save(deps.storage, &deps.api.addr_validate(&pauser)?)?;
+        Ok(Response::new()
+            .add_attribute("action", "set_pauser")
+            .add_attribute("pauser", pauser)
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_pause_state(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        minting: Option<bool>,
+        packing: Option<bool>,
+        trading: Option<bool>,
+        transfers: Option<bool>,
+    ) -> Result<Response<C>, ContractError> {
+        let pauser = PAUSER.load(deps.storage)?;
+        if pauser != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        let mut state = PAUSESTATE.may_load(deps.storage)?.unwrap_or_default();
+        state.minting = minting.unwrap_or(state.minting);
+        state.packing = packing.unwrap_or(state.packing);
+        state.trading = trading.unwrap_or(state.trading);
+        state.transfers = transfers.unwrap_or(state.transfers);
+        PAUSESTATE.save(deps.storage, &state)?;
         Ok(Response::new()
-            .add_attribute("action", "set_buy_cell_fee")
+            .add_attribute("action", "set_pause_state")
+            .add_attribute("minting", state.minting.to_string())
+            .add_attribute("packing", state.packing.to_string())
+            .add_attribute("trading", state.trading.to_string())
+            .add_attribute("transfers", state.transfers.to_string())
         )
     }
 
@@ -672,16 +1723,18 @@ where
         price: Uint128
     ) -> Result<Response<C>, ContractError> {
         let missing = ALLPACKABLENFTS.may_load(deps.storage, &token_id.clone())?;
-        if missing == None {
+        if missing.is_none() {
             return Err(ContractError::NoPackableToken {});
         }
         let mut token = ALLPACKABLENFTS.load(deps.storage, &token_id.clone())?;
         if token.current_owner != info.sender {
             return Err(ContractError::Unauthorized {});
         }
-        token.price = price;
+        let old_price = std::mem::replace(&mut token.price, price);
         ALLPACKABLENFTS.save(deps.storage, &token_id.clone(), &token)?;
+        let item = SaleItem::Nft { token_id: token_id.clone() };
         Ok(Response::new()
+            .add_event(price_change_event(&item, &info.sender, old_price, price))
             .add_attribute("action", "set_token_price")
             .add_attribute("token_id", token_id)
             .add_attribute("price", price.to_string())
@@ -696,17 +1749,20 @@ where
         pack_id: u64,
         price: Uint128
     ) -> Result<Response<C>, ContractError> {
-        let missing = ALLNFTPACKS.may_load(deps.storage, &pack_id.clone().to_string())?;
-        if missing == None {
+        let missing = all_nft_packs().may_load(deps.storage, pack_id)?;
+        if missing.is_none() {
             return Err(ContractError::NoPackableToken {});
         }
-        let mut pack = ALLNFTPACKS.load(deps.storage, &pack_id.clone().to_string())?;
+        let mut pack = all_nft_packs().load(deps.storage, pack_id)?;
         if pack.current_owner != info.sender {
             return Err(ContractError::Unauthorized {});
         }
-        pack.current_price = price;
-        ALLNFTPACKS.save(deps.storage, &pack_id.clone().to_string(), &pack)?;
+        let old_price = std::mem::replace(&mut pack.current_price, price);
+        all_nft_packs().save(deps.storage, pack_id, &pack)?;
+        let item = SaleItem::NftPack { pack_id };
+        update_listing(deps.storage, &item, listed_price(pack.for_sale, price))?;
         Ok(Response::new()
+            .add_event(price_change_event(&item, &info.sender, old_price, price))
             .add_attribute("action", "set_nft_pack_price")
             .add_attribute("pack_id", pack_id.to_string())
             .add_attribute("price", price.to_string())
@@ -721,17 +1777,20 @@ where
         pack_id: u64,
         price: Uint128
     ) -> Result<Response<C>, ContractError> {
-        let missing = ALLTOKENPACKS.may_load(deps.storage, &pack_id.clone().to_string())?;
-        if missing == None {
+        let missing = all_token_packs().may_load(deps.storage, pack_id)?;
+        if missing.is_none() {
             return Err(ContractError::NoPackableToken {});
         }
-        let mut pack = ALLTOKENPACKS.load(deps.storage, &pack_id.clone().to_string())?;
+        let mut pack = all_token_packs().load(deps.storage, pack_id)?;
         if pack.current_owner != info.sender {
             return Err(ContractError::Unauthorized {});
         }
-        pack.current_price = price;
-        ALLTOKENPACKS.save(deps.storage, &pack_id.clone().to_string(), &pack)?;
+        let old_price = std::mem::replace(&mut pack.current_price, price);
+        all_token_packs().save(deps.storage, pack_id, &pack)?;
+        let item = SaleItem::TokenPack { pack_id };
+        update_listing(deps.storage, &item, listed_price(pack.for_sale, price))?;
         Ok(Response::new()
+            .add_event(price_change_event(&item, &info.sender, old_price, price))
             .add_attribute("action", "set_token_pack_price")
             .add_attribute("pack_id", pack_id.to_string())
             .add_attribute("price", price.to_string())
@@ -747,13 +1806,17 @@ where
         for_sale: bool
     ) -> Result<Response<C>, ContractError> {
         let missing = ALLPACKABLENFTS.may_load(deps.storage, &token_id.clone())?;
-        if missing == None {
+        if missing.is_none() {
             return Err(ContractError::NoPackableToken {});
         }
         let mut token = ALLPACKABLENFTS.load(deps.storage, &token_id.clone())?;
         if token.current_owner != info.sender {
             return Err(ContractError::Unauthorized {});
         }
+        // there is no way to buy a single NFT, so it is never listed
+        if for_sale {
+            return Err(ContractError::NftSoldInPacks {});
+        }
         token.for_sale = for_sale;
         ALLPACKABLENFTS.save(deps.storage, &token_id.clone(), &token)?;
         Ok(Response::new()
@@ -771,17 +1834,20 @@ where
         pack_id: u64,
         for_sale: bool
     ) -> Result<Response<C>, ContractError> {
-        let missing = ALLNFTPACKS.may_load(deps.storage, &pack_id.clone().to_string())?;
-        if missing == None {
+        let missing = all_nft_packs().may_load(deps.storage, pack_id)?;
+        if missing.is_none() {
             return Err(ContractError::NoPackableToken {});
         }
-        let mut pack = ALLNFTPACKS.load(deps.storage, &pack_id.clone().to_string())?;
+        let mut pack = all_nft_packs().load(deps.storage, pack_id)?;
         if pack.current_owner != info.sender {
             return Err(ContractError::Unauthorized {});
         }
         pack.for_sale = for_sale;
-        ALLNFTPACKS.save(deps.storage, &pack_id.clone().to_string(), &pack)?;
+        all_nft_packs().save(deps.storage, pack_id, &pack)?;
+        let item = SaleItem::NftPack { pack_id };
+        update_listing(deps.storage, &item, listed_price(for_sale, pack.current_price))?;
         Ok(Response::new()
+            .add_event(listing_event(&item, &info.sender, for_sale))
             .add_attribute("action", "set_nft_pack_for_sale")
             .add_attribute("pack_id", pack_id.to_string())
             .add_attribute("for_sale", for_sale.to_string())
@@ -796,17 +1862,20 @@ where
         pack_id: u64,
         for_sale: bool
     ) -> Result<Response<C>, ContractError> {
-        let missing = ALLTOKENPACKS.may_load(deps.storage, &pack_id.clone().to_string())?;
-        if missing == None {
+        let missing = all_token_packs().may_load(deps.storage, pack_id)?;
+        if missing.is_none() {
             return Err(ContractError::NoPackableToken {});
         }
-        let mut pack = ALLTOKENPACKS.load(deps.storage, &pack_id.clone().to_string())?;
+        let mut pack = all_token_packs().load(deps.storage, pack_id)?;
         if pack.current_owner != info.sender {
             return Err(ContractError::Unauthorized {});
         }
         pack.for_sale = for_sale;
-        ALLTOKENPACKS.save(deps.storage, &pack_id.clone().to_string(), &pack)?;
+        all_token_packs().save(deps.storage, pack_id, &pack)?;
+        let item = SaleItem::TokenPack { pack_id };
+        update_listing(deps.storage, &item, listed_price(for_sale, pack.current_price))?;
         Ok(Response::new()
+            .add_event(listing_event(&item, &info.sender, for_sale))
             .add_attribute("action", "set_token_pack_for_sale")
             .add_attribute("pack_id", pack_id.to_string())
             .add_attribute("for_sale", for_sale.to_string())
@@ -831,9 +1900,11 @@ where
         recipient: String,
         token_id: String,
     ) -> Result<Response<C>, ContractError> {
-        self._transfer_nft(deps, &env, &info, &recipient, &token_id)?;
+        let from = self.tokens.load(deps.storage, &token_id)?.owner;
+        let token = self._transfer_nft(deps, &env, &info, &recipient, &token_id)?;
 
         Ok(Response::new()
+            .add_event(transfer_event(&SaleItem::Nft { token_id: token_id.clone() }, &from, &token.owner))
             .add_attribute("action", "transfer_nft")
             .add_attribute("sender", info.sender)
             .add_attribute("recipient", recipient)
@@ -850,7 +1921,8 @@ where
         msg: Binary,
     ) -> Result<Response<C>, ContractError> {
         // Transfer token
-        self._transfer_nft(deps, &env, &info, &contract, &token_id)?;
+        let from = self.tokens.load(deps.storage, &token_id)?.owner;
+        let token = self._transfer_nft(deps, &env, &info, &contract, &token_id)?;
 
         let send = Cw721ReceiveMsg {
             sender: info.sender.to_string(),
@@ -861,6 +1933,7 @@ where
         // Send message
         Ok(Response::new()
             .add_message(send.into_cosmos_msg(contract.clone())?)
+            .add_event(transfer_event(&SaleItem::Nft { token_id: token_id.clone() }, &from, &token.owner))
             .add_attribute("action", "send_nft")
             .add_attribute("sender", info.sender)
             .add_attribute("recipient", contract)
@@ -970,6 +2043,138 @@ where
     T: Serialize + DeserializeOwned + Clone,
     C: CustomMsg,
 {
+    /// validates a whole batch against the minter quota, the supply cap, storage and itself
+    pub fn check_batch_mint(
+        &self,
+        deps: Deps,
+        minter: &MinterInfo,
+        mints: &[MintMsg<T>],
+    ) -> Result<(), ContractError> {
+        let count = mints.len() as u64;
+        if let Some(quota) = minter.quota {
+            if minter.minted + count > quota {
+                return Err(ContractError::MintQuotaExceeded {});
+            }
+        }
+        // the placeholder replaces the URI of hidden mints
+        self.check_mint_entries(deps, mints, !hidden_drop_active(deps.storage)?)
+    }
+
+    /// validates new entries against the supply cap, storage and themselves
+    pub fn check_mint_entries(
+        &self,
+        deps: Deps,
+        mints: &[MintMsg<T>],
+        check_uri: bool,
+    ) -> Result<(), ContractError> {
+        let count = mints.len() as u64;
+        let con = CONFIG.load(deps.storage)?;
+        if self.token_count(deps.storage)? + queued_mint_count(deps.storage)? + count > con.max_packable_nft {
+            return Err(ContractError::PackableNftSupplyExceeded {});
+        }
+        for (i, msg) in mints.iter().enumerate() {
+            let previous = &mints[..i];
+            if previous.iter().any(|m| m.token_id == msg.token_id)
+                || self.tokens.may_load(deps.storage, &msg.token_id)?.is_some()
+                || MINTQUEUETOKENIDS.has(deps.storage, &msg.token_id)
+            {
+                return Err(ContractError::Claimed {});
+            }
+            if check_uri && (previous.iter().any(|m| m.token_uri == msg.token_uri)
+                || TOKENURIEXISTS.may_load(deps.storage, &msg.token_uri)?.unwrap_or(false))
+            {
+                return Err(ContractError::ExistTokenUri {});
+            }
+            check_royalty_fee(&con, msg.royalty_fee.unwrap_or_default())?;
+            if previous.iter().any(|m| name_key(&m.name) == name_key(&msg.name))
+                || TOKENNAMEEXISTS.has(deps.storage, &name_key(&msg.name))
+            {
+                return Err(ContractError::ExistTokenName {});
+            }
+        }
+        Ok(())
+    }
+
+    /// registers the final URI of a token minted behind a placeholder
+    fn set_token_uri(&self, storage: &mut dyn Storage, token_id: &str, token_uri: String) -> StdResult<()> {
+        TOKENURIEXISTS.save(storage, &token_uri, &true)?;
+        let mut token = self.tokens.load(storage, token_id)?;
+        token.token_uri = token_uri.clone();
+        self.tokens.save(storage, token_id, &token)?;
+        ALLPACKABLENFTS.update(storage, token_id, |old| -> StdResult<_> {
+            let mut packable_token = old.ok_or_else(|| StdError::not_found("PackableToken"))?;
+            packable_token.token_uri = token_uri;
+            Ok(packable_token)
+        })?;
+        Ok(())
+    }
+
+    /// creates the token and its packable record, the caller has to validate the mint.
+    /// `reserved` marks a queue entry holding a slot of the hidden drop
+    pub fn _mint_packable(
+        &self,
+        storage: &mut dyn Storage,
+        minter: &Addr,
+        owner: Addr,
+        msg: MintMsg<T>,
+        reserved: bool,
+    ) -> Result<Event, ContractError> {
+        // tokens minted during an unrevealed drop get the placeholder and the next drop index
+        let token_uri = match HIDDENDROP.may_load(storage)?.filter(|d| reserved || d.base_uri.is_none()) {
+            Some(mut drop) => {
+                if reserved {
+                    drop.reserved -= 1;
+                } else if drop.minted + drop.reserved >= drop.drop_size {
+                    return Err(ContractError::HiddenDropFull {});
+                }
+                HIDDENTOKENS.save(storage, &msg.token_id, &drop.minted)?;
+                drop.minted += 1;
+                HIDDENDROP.save(storage, &drop)?;
+                drop.placeholder_uri
+            }
+            None => {
+                TOKENURIEXISTS.save(storage, &msg.token_uri, &true)?;
+                msg.token_uri.clone()
+            }
+        };
+        let event = mint_event(
+            &msg.token_id, &owner, minter, &token_uri, &msg.name, msg.price, msg.royalty_fee.unwrap_or_default()
+        );
+        // create the token
+        let token = TokenInfo {
+            owner: owner.clone(),
+            approvals: vec![],
+            token_uri: token_uri.clone(),
+            extension: msg.extension.clone()
+        };
+        self.tokens
+            .update(storage, &msg.token_id.clone(), |old| match old {
+                Some(_) => Err(ContractError::Claimed {}),
+                None => Ok(token),
+            })?;
+        self.increment_tokens(storage)?;
+
+        let packable_token = PackableToken {
+            token_id: msg.token_id.clone(),
+            token_name: msg.name.clone(),
+            token_uri,
+            minted_by: minter.clone(),
+            current_owner: owner,
+            previous_owner: None,
+            price: msg.price,
+            number_of_transfers: Uint128::zero(),
+            for_sale: false,
+            royalty_fee: msg.royalty_fee.unwrap_or_default(),
+        };
+        ALLPACKABLENFTS
+            .update(storage, &msg.token_id.clone(), |old| match old {
+                Some(_) => Err(ContractError::Claimed {}),
+                None => Ok(packable_token),
+            })?;
+        TOKENNAMEEXISTS.save(storage, &name_key(&msg.name), &true)?;
+        Ok(event)
+    }
+
     pub fn _transfer_nft(
         &self,
         deps: DepsMut,
@@ -982,9 +2187,11 @@ where
         // ensure we have permissions
         self.check_can_send(deps.as_ref(), env, info, &token)?;
         // set owner and remove existing approvals
-        token.owner = deps.api.addr_validate(recipient)?;
+        let previous_owner = std::mem::replace(&mut token.owner, deps.api.addr_validate(recipient)?);
         token.approvals = vec![];
         self.tokens.save(deps.storage, token_id, &token)?;
+        set_packable_owner(deps.storage, token_id, &token.owner)?;
+        move_holdings(deps.storage, &previous_owner, &token.owner, 1)?;
         Ok(token)
     }
 
@@ -1006,11 +2213,7 @@ where
 
         // update the approval list (remove any for the same spender before adding)
         let spender_addr = deps.api.addr_validate(spender)?;
-        token.approvals = token
-            .approvals
-            .into_iter()
-            .filter(|apr| apr.spender != spender_addr)
-            .collect();
+        token.approvals.retain(|apr| apr.spender != spender_addr);
 
         // only difference between approve and revoke
         if add {
@@ -1031,6 +2234,70 @@ where
         Ok(token)
     }
 
+    /// fails if the operation behind `msg` is paused. Every message is listed, so a new one needs a decision here.
+    /// Admin messages and revoking approvals are never paused, neither are unpacking and removing
+    /// pack items, so owners can always withdraw what this contract holds for them
+    pub fn check_not_paused(&self, deps: Deps, msg: &ExecuteMsg<T>) -> Result<(), ContractError> {
+        let state = PAUSESTATE.may_load(deps.storage)?.unwrap_or_default();
+        let (paused, operation) = match msg {
+            ExecuteMsg::MintPackable(_)
+            | ExecuteMsg::BatchMintPackable { .. }
+            | ExecuteMsg::MintPack { .. }
+            | ExecuteMsg::PublicMint { .. } => (state.minting, "minting"),
+            ExecuteMsg::PackNfts { .. }
+            | ExecuteMsg::PackTokens { .. }
+            | ExecuteMsg::AddToNftPack { .. }
+            | ExecuteMsg::RenamePack { .. }
+            | ExecuteMsg::UpdateNftPackMetadata { .. }
+            | ExecuteMsg::UpdateTokenPackMetadata { .. } => (state.packing, "packing"),
+            ExecuteMsg::BuyNftPack { .. }
+            | ExecuteMsg::BuyTokenPack { .. }
+            | ExecuteMsg::SetTokenPrice { .. }
+            | ExecuteMsg::SetNftPackPrice { .. }
+            | ExecuteMsg::SetTokenPackPrice { .. }
+            | ExecuteMsg::SetTokenForSale { .. }
+            | ExecuteMsg::SetNftPackForSale { .. }
+            | ExecuteMsg::SetTokenPackForSale { .. }
+            | ExecuteMsg::UpdateRoyaltyFee { .. }
+            | ExecuteMsg::RenounceRoyalty { .. }
+            | ExecuteMsg::SetRoyaltyRecipient { .. } => (state.trading, "trading"),
+            ExecuteMsg::TransferNft { .. }
+            | ExecuteMsg::SendNft { .. }
+            | ExecuteMsg::TransferNftPack { .. }
+            | ExecuteMsg::TransferTokenPack { .. }
+            | ExecuteMsg::BurnPackable { .. }
+            | ExecuteMsg::Approve { .. }
+            | ExecuteMsg::ApproveAll { .. }
+            | ExecuteMsg::ApproveNftPack { .. }
+            | ExecuteMsg::ApproveTokenPack { .. } => (state.transfers, "transfers"),
+            ExecuteMsg::UnpackNfts { .. }
+            | ExecuteMsg::UnpackTokens { .. }
+            | ExecuteMsg::RemoveFromNftPack { .. }
+            | ExecuteMsg::Revoke { .. }
+            | ExecuteMsg::RevokeAll { .. }
+            | ExecuteMsg::AddMinter { .. }
+            | ExecuteMsg::RemoveMinter { .. }
+            | ExecuteMsg::RenounceMinter {}
+            | ExecuteMsg::EnqueueMints { .. }
+            | ExecuteMsg::AddMintPhase { .. }
+            | ExecuteMsg::RemoveMintPhase { .. }
+            | ExecuteMsg::SetCreatorShares { .. }
+            | ExecuteMsg::StartHiddenDrop { .. }
+            | ExecuteMsg::Reveal { .. }
+            | ExecuteMsg::RevealTokens { .. }
+            | ExecuteMsg::ResolveRevealConflict { .. }
+            | ExecuteMsg::UpdateConfig { .. }
+            | ExecuteMsg::ProposeNewOwner { .. }
+            | ExecuteMsg::AcceptOwnership {}
+            | ExecuteMsg::SetPauser { .. }
+            | ExecuteMsg::SetPauseState { .. } => (false, ""),
+        };
+        if paused {
+            return Err(ContractError::Paused { operation: operation.to_string() });
+        }
+        Ok(())
+    }
+
     /// returns true iff the sender can execute approve or reject on the contract
     pub fn check_can_approve(
         &self,
@@ -1097,3 +2364,246 @@ where
         }
     }
 }
+
+/// a pack name must keep a non-empty registry key
+fn check_pack_name(name: &str) -> Result<(), ContractError> {
+    if name_key(name).is_empty() {
+        return Err(ContractError::InvalidPackName {});
+    }
+    Ok(())
+}
+
+/// validates a pack about to be opened with `token_ids`
+fn check_new_nft_pack(
+    storage: &dyn Storage,
+    pack_name: &str,
+    royalty_fee: Decimal,
+    token_ids: &[String],
+) -> Result<(), ContractError> {
+    check_pack_name(pack_name)?;
+    if PACKNAMEEXISTS.has(storage, &name_key(pack_name)) {
+        return Err(ContractError::ExistPackName {});
+    }
+    let con = CONFIG.load(storage)?;
+    if token_ids.is_empty() {
+        return Err(ContractError::NoPackItems {});
+    }
+    if token_ids.len() as u64 > con.max_pack_item_count {
+        return Err(ContractError::PackItemCountExceeded {});
+    }
+    check_duplicate_token_ids(token_ids)?;
+    check_royalty_fee(&con, royalty_fee)?;
+    Ok(())
+}
+
+/// opens a pack of items already owned by this contract, with `owner` as first royalty owner
+fn create_nft_pack(
+    storage: &mut dyn Storage,
+    owner: &Addr,
+    pack_name: &str,
+    pack_items: Vec<String>,
+    price: Uint128,
+    royalty_fee: Decimal,
+    metadata: PackMetadata,
+) -> Result<u64, ContractError> {
+    // increase pack count
+    let pack_count = NFTPACKCOUNTER.load(storage)? + 1;
+    NFTPACKCOUNTER.save(storage, &pack_count)?;
+
+    let nft_pack = NftPack {
+        pack_id: pack_count,
+        pack_name: pack_name.to_string(),
+        item_count: pack_items.len(),
+        pack_items,
+        minted_by: owner.clone(),
+        current_owner: owner.clone(),
+        previous_owner: None,
+        current_price: price,
+        previous_price: Uint128::zero(),
+        number_of_transfers: 0u64,
+        for_sale: true,
+        royalty_owners: vec![owner.clone()],
+        approvals: vec![],
+        metadata
+    };
+    // save all NftPack
+    all_nft_packs().save(storage, pack_count, &nft_pack)?;
+    for tag in nft_pack.metadata.tags.iter() {
+        PACKTAGS.save(storage, (tag, pack_count), &true)?;
+    }
+    //update pack name exists
+    PACKNAMEEXISTS.save(storage, &name_key(pack_name), &true)?;
+
+    ROYALTYFEES.save(storage, (pack_count, owner.as_str()), &royalty_fee)?;
+    record_chain_royalty(storage, royalty_fee)?;
+    add_holdings(storage, owner, 1)?;
+    update_listing(storage, &SaleItem::NftPack { pack_id: pack_count }, Some(price))?;
+    update_pack_count(storage, PackKind::Nft, true)?;
+    Ok(pack_count)
+}
+
+/// lowercases and dedups the tags of pack metadata, and keeps every field within its bound
+fn normalize_pack_metadata(mut metadata: PackMetadata) -> Result<PackMetadata, ContractError> {
+    let mut tags: Vec<String> = vec![];
+    for tag in metadata.tags.iter() {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || tag.len() > MAX_PACK_TAG_LENGTH {
+            return Err(ContractError::InvalidPackTag { tag });
+        }
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    if tags.len() > MAX_PACK_TAGS {
+        return Err(ContractError::PackTagCountExceeded { max: MAX_PACK_TAGS });
+    }
+    metadata.tags = tags;
+
+    if metadata.description.as_ref().is_some_and(|d| d.len() > MAX_PACK_DESCRIPTION_LENGTH) {
+        return Err(ContractError::PackMetadataTooLong { field: "description".to_string(), max: MAX_PACK_DESCRIPTION_LENGTH });
+    }
+    if metadata.cover_image.as_ref().is_some_and(|uri| uri.len() > MAX_PACK_URI_LENGTH) {
+        return Err(ContractError::PackMetadataTooLong { field: "cover_image".to_string(), max: MAX_PACK_URI_LENGTH });
+    }
+    if metadata.attributes.len() > MAX_PACK_ATTRIBUTES {
+        return Err(ContractError::PackAttributeCountExceeded { max: MAX_PACK_ATTRIBUTES });
+    }
+    for attribute in metadata.attributes.iter() {
+        if attribute.trait_type.len() > MAX_PACK_ATTRIBUTE_LENGTH || attribute.value.len() > MAX_PACK_ATTRIBUTE_LENGTH {
+            return Err(ContractError::PackMetadataTooLong { field: "attribute".to_string(), max: MAX_PACK_ATTRIBUTE_LENGTH });
+        }
+    }
+    Ok(metadata)
+}
+
+/// pays an overpayment back to the buyer, net of tax like any native payout
+fn refund_msg<C>(
+    querier: &QuerierWrapper,
+    con: &Config,
+    info: &AssetInfo,
+    amount: Uint128,
+    recipient: &Addr,
+) -> StdResult<Option<CosmosMsg<C>>> {
+    if amount.is_zero() {
+        return Ok(None);
+    }
+    let mut refund = Asset { info: info.clone(), amount };
+    if con.deduct_tax && info.is_native_token() {
+        refund.amount = refund.deduct_tax(querier)?.amount;
+    }
+    Ok(Some(refund.into_msg(querier, recipient.clone())?))
+}
+
+/// takes an item off the market, with a `delist` event if it was listed
+fn delist(storage: &mut dyn Storage, item: &SaleItem, owner: &Addr) -> StdResult<Option<Event>> {
+    let listed = LISTINGS.has(storage, &item.key());
+    update_listing(storage, item, None)?;
+    Ok(listed.then(|| listing_event(item, owner, false)))
+}
+
+fn hidden_drop_active(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(HIDDENDROP.may_load(storage)?.is_some_and(|d| d.base_uri.is_none()))
+}
+
+/// number of NFTs registered in the mint queue and not minted yet
+fn queued_mint_count(storage: &dyn Storage) -> StdResult<u64> {
+    let head = MINTQUEUEHEAD.may_load(storage)?.unwrap_or_default();
+    let tail = MINTQUEUETAIL.may_load(storage)?.unwrap_or_default();
+    Ok(tail - head)
+}
+
+/// limits must be non zero and the supply cap can't drop below the minted and queued count
+fn check_collection_limits(con: &Config, token_count: u64) -> Result<(), ContractError> {
+    if con.max_packable_nft == 0
+        || con.max_pack_item_count == 0
+        || con.max_royalyty_owner == 0
+        || con.max_packable_nft < token_count
+    {
+        return Err(ContractError::InvalidCollectionLimit {});
+    }
+    Ok(())
+}
+
+/// native denom as accepted by the bank module: a letter followed by 2 to 127 of `[a-zA-Z0-9/:._-]`
+fn check_sale_denom(denom: &str) -> Result<(), ContractError> {
+    let mut chars = denom.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && (3..=128).contains(&denom.len())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c));
+    if !valid {
+        return Err(ContractError::InvalidSaleDenom { denom: denom.to_string() });
+    }
+    Ok(())
+}
+
+fn check_buy_sell_fee(fee: Decimal) -> Result<(), ContractError> {
+    if fee > Decimal::percent(MAX_BUY_SELL_FEE_PERCENT) {
+        return Err(ContractError::InvalidBuySellFee { max_percent: MAX_BUY_SELL_FEE_PERCENT });
+    }
+    Ok(())
+}
+
+/// bounds a single royalty rate, the sum over a royalty chain is checked by `join_royalty_chain`
+fn check_royalty_fee(con: &Config, royalty_fee: Decimal) -> Result<(), ContractError> {
+    if royalty_fee > con.max_royalty_fee {
+        return Err(ContractError::RoyaltyFeeTooHigh { max: con.max_royalty_fee });
+    }
+    Ok(())
+}
+
+/// `max_royalty_fee * max_royalty_owner + buy_sell_fee` must not exceed 100%, so a full royalty
+/// chain at the highest rate still fits the price. Checked on every config change
+fn check_max_royalty_fee(con: &Config) -> Result<(), ContractError> {
+    let bound = max_royalty_fee_bound(con.buy_sell_fee, con.max_royalyty_owner);
+    if con.max_royalty_fee > bound {
+        return Err(ContractError::RoyaltyFeeTooHigh { max: bound });
+    }
+    Ok(())
+}
+
+/// the royalty rate of `owner` on a pack, an error if `owner` is not in its royalty chain
+fn load_royalty_fee(storage: &dyn Storage, kind: PackKind, pack_id: u64, owner: &Addr) -> Result<Decimal, ContractError> {
+    let (fees, _) = royalty_maps(kind);
+    fees.may_load(storage, (pack_id, owner.as_str()))?.ok_or(match kind {
+        PackKind::Nft => ContractError::NoNftPackRoyalty {},
+        PackKind::Token => ContractError::NoTokenPackRoyalty {},
+    })
+}
+
+fn check_duplicate_token_ids(token_ids: &[String]) -> Result<(), ContractError> {
+    for (i, token_id) in token_ids.iter().enumerate() {
+        if token_ids[..i].contains(token_id) {
+            return Err(ContractError::DuplicateTokenId { token_id: token_id.clone() });
+        }
+    }
+    Ok(())
+}
+
+/// appends a new owner of a pack to its royalty chain at `royalty_fee`,
+/// an owner already on the chain keeps its position and rate. A full chain stops growing,
+/// the pack still changes hands but its new owner earns no royalty.
+/// The rates of the chain and the current buy/sell fee must leave a payout for the seller
+fn join_royalty_chain(
+    storage: &mut dyn Storage,
+    con: &Config,
+    kind: PackKind,
+    pack_id: u64,
+    royalty_owners: &mut Vec<Addr>,
+    owner: &Addr,
+    royalty_fee: Decimal,
+) -> Result<(), ContractError> {
+    if royalty_owners.contains(owner) || royalty_owners.len() as u64 >= con.max_royalyty_owner {
+        return Ok(());
+    }
+    royalty_owners.push(owner.clone());
+    check_royalty_fee(con, royalty_fee)?;
+    let (royalty_fees, _) = royalty_maps(kind);
+    royalty_fees.save(storage, (pack_id, owner.as_str()), &royalty_fee)?;
+    let royalty_total = chain_royalty(storage, kind, pack_id, royalty_owners)?;
+    let total = add_rates(con.buy_sell_fee, royalty_total)?;
+    if total > Decimal::one() {
+        return Err(ContractError::RoyaltyChainTooHigh { total });
+    }
+    record_chain_royalty(storage, royalty_total)?;
+    Ok(())
+}
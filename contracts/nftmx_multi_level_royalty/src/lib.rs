@@ -1,18 +1,24 @@
 mod error;
+pub mod events;
 mod execute;
 pub mod helpers;
+pub mod merkle;
+pub mod metadata;
+mod migrate;
 pub mod msg;
 mod query;
+mod settlement;
 pub mod state;
 pub mod asset;
 
 pub use crate::error::ContractError;
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, MinterResponse, QueryMsg};
+pub use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, MintMsg, MinterResponse, QueryMsg};
+pub use crate::metadata::{Metadata, Trait};
 pub use crate::state::Cw721Contract;
 use cosmwasm_std::Empty;
 
-// This is a simple type to let us handle empty extensions
-pub type Extension = Option<Empty>;
+// On-chain metadata, tokens can still be minted without any
+pub type Extension = Option<Metadata>;
 
 #[cfg(not(feature = "library"))]
 pub mod entry {
@@ -28,7 +34,7 @@ pub mod entry {
         env: Env,
         info: MessageInfo,
         msg: InstantiateMsg,
-    ) -> StdResult<Response> {
+    ) -> Result<Response, ContractError> {
         let tract = Cw721Contract::<Extension, Empty>::default();
         tract.instantiate(deps, env, info, msg)
     }
@@ -47,6 +53,12 @@ pub mod entry {
     #[entry_point]
     pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         let tract = Cw721Contract::<Extension, Empty>::default();
-        tract.query(deps, env, msg)
+        tract.query_with_rarity(deps, env, msg)
+    }
+
+    #[entry_point]
+    pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+        let tract = Cw721Contract::<Extension, Empty>::default();
+        tract.migrate(deps, env, msg)
     }
 }
@@ -0,0 +1,104 @@
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+
+/// Leaf of an allowlist entry: sha256(address || allowance as big endian u64)
+pub fn allowlist_leaf(address: &str, allowance: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(address.as_bytes());
+    hasher.update(allowance.to_be_bytes());
+    hasher.finalize().into()
+}
+
+/// Walks `proof` from `leaf` up to the tree root and compares it with `root`.
+/// Sibling pairs are hashed in sorted order, so proofs don't carry left/right flags
+pub fn verify_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(leaf, |hash, sibling| {
+        let (first, second) = if hash <= *sibling {
+            (hash, *sibling)
+        } else {
+            (*sibling, hash)
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(first);
+        hasher.update(second);
+        hasher.finalize().into()
+    });
+    computed == *root
+}
+
+/// Decodes a hex encoded sha256 hash
+pub fn parse_hash(hash: &str) -> Result<[u8; 32], ContractError> {
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(hash, &mut bytes).map_err(|_| ContractError::InvalidMerkleHash {
+        hash: hash.to_string(),
+    })?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        let mut hasher = Sha256::new();
+        hasher.update(first);
+        hasher.update(second);
+        hasher.finalize().into()
+    }
+
+    /// leaves of a four entry allowlist, with the root of their tree
+    fn tree() -> ([[u8; 32]; 4], [u8; 32]) {
+        let leaves = [
+            allowlist_leaf("alice", 2),
+            allowlist_leaf("bob", 1),
+            allowlist_leaf("carol", 5),
+            allowlist_leaf("dave", 3),
+        ];
+        let root = hash_pair(hash_pair(leaves[0], leaves[1]), hash_pair(leaves[2], leaves[3]));
+        (leaves, root)
+    }
+
+    #[test]
+    fn valid_multi_level_proof() {
+        let (leaves, root) = tree();
+        let proof = [leaves[1], hash_pair(leaves[2], leaves[3])];
+        assert!(verify_proof(&root, allowlist_leaf("alice", 2), &proof));
+        let proof = [leaves[2], hash_pair(leaves[0], leaves[1])];
+        assert!(verify_proof(&root, allowlist_leaf("dave", 3), &proof));
+    }
+
+    #[test]
+    fn wrong_allowance() {
+        let (leaves, root) = tree();
+        let proof = [leaves[1], hash_pair(leaves[2], leaves[3])];
+        assert!(!verify_proof(&root, allowlist_leaf("alice", 3), &proof));
+    }
+
+    #[test]
+    fn wrong_address() {
+        let (leaves, root) = tree();
+        let proof = [leaves[1], hash_pair(leaves[2], leaves[3])];
+        assert!(!verify_proof(&root, allowlist_leaf("mallory", 2), &proof));
+    }
+
+    #[test]
+    fn empty_proof_of_single_leaf() {
+        let leaf = allowlist_leaf("alice", 2);
+        assert!(verify_proof(&leaf, leaf, &[]));
+        assert!(!verify_proof(&leaf, allowlist_leaf("bob", 2), &[]));
+    }
+
+    #[test]
+    fn parse_hash_roundtrip_and_malformed_hex() {
+        let (_, root) = tree();
+        assert_eq!(parse_hash(&hex::encode(root)).unwrap(), root);
+        for hash in ["xyz", "abcd", &"g".repeat(64), &"0".repeat(66)] {
+            assert_eq!(
+                parse_hash(hash).unwrap_err(),
+                ContractError::InvalidMerkleHash { hash: hash.to_string() }
+            );
+        }
+    }
+}
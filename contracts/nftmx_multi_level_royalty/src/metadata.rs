@@ -0,0 +1,45 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::Empty;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct Trait {
+    pub trait_type: String,
+    pub value: String,
+}
+
+/// On-chain metadata in the style of cw721-metadata-onchain,
+/// see https://docs.opensea.io/docs/metadata-standards
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct Metadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub attributes: Option<Vec<Trait>>,
+    pub animation_url: Option<String>,
+    pub external_url: Option<String>,
+}
+
+/// Gives pack queries access to the traits of any extension type
+pub trait HasAttributes {
+    fn attributes(&self) -> Vec<Trait>;
+}
+
+impl HasAttributes for Empty {
+    fn attributes(&self) -> Vec<Trait> {
+        vec![]
+    }
+}
+
+impl HasAttributes for Metadata {
+    fn attributes(&self) -> Vec<Trait> {
+        self.attributes.clone().unwrap_or_default()
+    }
+}
+
+impl<T: HasAttributes> HasAttributes for Option<T> {
+    fn attributes(&self) -> Vec<Trait> {
+        self.as_ref().map(|x| x.attributes()).unwrap_or_default()
+    }
+}
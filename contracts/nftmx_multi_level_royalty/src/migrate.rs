@@ -0,0 +1,253 @@
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, Decimal, DepsMut, Env, Order, Response, StdError, StdResult, Storage};
+
+use cw2::{get_contract_version, set_contract_version};
+use cw721::CustomMsg;
+use cw_storage_plus::{Item, Map};
+
+use crate::error::ContractError;
+use crate::execute::{CONTRACT_NAME, CONTRACT_VERSION};
+use crate::msg::{MigrateMsg, PackKind, SaleItem};
+use crate::state::{
+    Config, Cw721Contract, MinterInfo, PackMetadataEditor, PauseState, CONFIG, MINTERS, PAUSER,
+    PAUSESTATE, PACKNAMEEXISTS, default_sale_denom, max_royalty_fee_bound, MAX_BUY_SELL_FEE_PERCENT, default_deduct_tax, TOKENNAMEEXISTS, TOKENPACKNAMEEXISTS, name_key,
+    all_nft_packs, ALLPACKABLENFTS, all_token_packs, NftPack, TokenPack, ROYALTYFEES, TOKENROYALTYFEES, add_holdings, listed_price, update_listing, update_pack_count,
+    chain_royalty, record_chain_royalty,
+};
+
+// name stored by versions before 0.2.0
+const LEGACY_CONTRACT_NAME: &str = "crates.io:cw721-base";
+
+/// Config layout before 0.2.0, without the fee collector
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+struct ConfigV0_1 {
+    pub collection_name: String,
+    pub collection_name_symbol: String,
+    pub max_packable_nft: u64,
+    pub max_pack_item_count: u64,
+    pub max_royalyty_owner: u64,
+    pub buy_sell_fee: Decimal,
+    pub contract_owner: Addr,
+}
+
+const CONFIGV0_1: Item<ConfigV0_1> = Item::new("config");
+// packs and royalty rates were keyed by the pack id string before 0.2.0
+const ALLNFTPACKSV0_1: Map<&str, NftPack> = Map::new("all_nft_packs");
+const ALLTOKENPACKSV0_1: Map<&str, TokenPack> = Map::new("all_token_packs");
+const ROYALTYFEESV0_1: Map<(&str, &str), Decimal> = Map::new("royalty_fees");
+const TOKENROYALTYFEESV0_1: Map<(&str, &str), Decimal> = Map::new("token_royalty_fees");
+// pack counts stored before 0.2.0, now derived from the pack owner indexes
+const NFTPACKBALANCESV0_1: Map<&str, u64> = Map::new("nft_pack_balances");
+const TOKENPACKBALANCESV0_1: Map<&str, u64> = Map::new("token_pack_balances");
+
+impl<'a, T, C> Cw721Contract<'a, T, C>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    C: CustomMsg,
+{
+    /// Upgrades the stored state to the current version, running every
+    /// migration step newer than the stored version in order.
+    /// Migrating to an older version is refused
+    pub fn migrate(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        _msg: MigrateMsg,
+    ) -> Result<Response<C>, ContractError> {
+        let stored = get_contract_version(deps.storage)?;
+        if stored.contract != CONTRACT_NAME && stored.contract != LEGACY_CONTRACT_NAME {
+            return Err(ContractError::InvalidMigrationContract { name: stored.contract });
+        }
+        let stored_version = parse_version(&stored.version)?;
+        if stored_version > parse_version(CONTRACT_VERSION)? {
+            return Err(ContractError::InvalidMigrationVersion {
+                stored: stored.version,
+                current: CONTRACT_VERSION.to_string(),
+            });
+        }
+
+        if stored_version < (0, 2, 0) {
+            let minter = self.minter.load(deps.storage)?;
+            migrate_v0_2(deps.storage, &minter)?;
+            migrate_pack_keys(deps.storage)?;
+            self.backfill_collection_stats(deps.storage, &env.contract.address)?;
+        }
+
+        set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+        Ok(Response::new()
+            .add_attribute("action", "migrate")
+            .add_attribute("from_version", stored.version)
+            .add_attribute("to_version", CONTRACT_VERSION))
+    }
+
+    /// builds the collection aggregates that later versions keep up to date on every change
+    fn backfill_collection_stats(&self, storage: &mut dyn Storage, contract: &Addr) -> StdResult<()> {
+        let owners = self
+            .tokens
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, token)| token.owner))
+            .collect::<StdResult<Vec<Addr>>>()?;
+        for owner in owners.iter().filter(|owner| *owner != contract) {
+            add_holdings(storage, owner, 1)?;
+        }
+
+        let nfts = ALLPACKABLENFTS
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (token_id, mut nft) in nfts {
+            // earlier versions left the packable owner behind on transfers and listed NFTs no one could buy
+            let owner = self.tokens.load(storage, &token_id)?.owner;
+            if nft.current_owner != owner {
+                nft.previous_owner = Some(std::mem::replace(&mut nft.current_owner, owner));
+            }
+            nft.for_sale = false;
+            ALLPACKABLENFTS.save(storage, &token_id, &nft)?;
+        }
+        let nft_packs = all_nft_packs()
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (_, pack) in nft_packs {
+            add_holdings(storage, &pack.current_owner, 1)?;
+            let royalty_total = chain_royalty(storage, PackKind::Nft, pack.pack_id, &pack.royalty_owners)?;
+            record_chain_royalty(storage, royalty_total)?;
+            let item = SaleItem::NftPack { pack_id: pack.pack_id };
+            update_listing(storage, &item, listed_price(pack.for_sale, pack.current_price))?;
+            update_pack_count(storage, PackKind::Nft, true)?;
+        }
+        let token_packs = all_token_packs()
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (_, pack) in token_packs {
+            add_holdings(storage, &pack.current_owner, 1)?;
+            let royalty_total = chain_royalty(storage, PackKind::Token, pack.pack_id, &pack.royalty_owners)?;
+            record_chain_royalty(storage, royalty_total)?;
+            let item = SaleItem::TokenPack { pack_id: pack.pack_id };
+            update_listing(storage, &item, listed_price(pack.for_sale, pack.current_price))?;
+            update_pack_count(storage, PackKind::Token, true)?;
+        }
+        Ok(())
+    }
+}
+
+/// 0.2.0 adds the fee collector, the pause state and the minter set, and makes names case-insensitive.
+/// A buy/sell fee above the allowed bound was never intended and is reset to zero
+fn migrate_v0_2(storage: &mut dyn Storage, minter: &Addr) -> StdResult<()> {
+    let old = CONFIGV0_1.load(storage)?;
+    let buy_sell_fee = if old.buy_sell_fee > Decimal::percent(MAX_BUY_SELL_FEE_PERCENT) {
+        Decimal::zero()
+    } else {
+        old.buy_sell_fee
+    };
+    let con = Config {
+        collection_name: old.collection_name,
+        collection_name_symbol: old.collection_name_symbol,
+        max_packable_nft: old.max_packable_nft,
+        max_pack_item_count: old.max_pack_item_count,
+        max_royalyty_owner: old.max_royalyty_owner,
+        buy_sell_fee,
+        contract_owner: old.contract_owner.clone(),
+        fee_collector: old.contract_owner.clone(),
+        pack_metadata_editor: PackMetadataEditor::default(),
+        deduct_tax: default_deduct_tax(),
+        sale_denom: default_sale_denom(),
+        max_royalty_fee: max_royalty_fee_bound(buy_sell_fee, old.max_royalyty_owner),
+    };
+    CONFIG.save(storage, &con)?;
+    PAUSER.save(storage, &old.contract_owner)?;
+    PAUSESTATE.save(storage, &PauseState::default())?;
+    MINTERS.save(storage, minter, &MinterInfo { quota: None, minted: 0u64 })?;
+    for registry in [TOKENNAMEEXISTS, PACKNAMEEXISTS, TOKENPACKNAMEEXISTS] {
+        migrate_name_registry(storage, registry)?;
+    }
+    Ok(())
+}
+
+/// Rekeys packs and their royalty rates by the numeric pack id. The old and new keys share a namespace,
+/// so every legacy entry is read and removed before the new ones are written.
+/// Saving through the indexed maps builds the owner and minter indexes
+fn migrate_pack_keys(storage: &mut dyn Storage) -> StdResult<()> {
+    let nft_packs = ALLNFTPACKSV0_1
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, NftPack)>>>()?;
+    let token_packs = ALLTOKENPACKSV0_1
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, TokenPack)>>>()?;
+    let royalty_fees = ROYALTYFEESV0_1
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<((String, String), Decimal)>>>()?;
+    let token_royalty_fees = TOKENROYALTYFEESV0_1
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<((String, String), Decimal)>>>()?;
+
+    for (pack_id, _) in nft_packs.iter() {
+        ALLNFTPACKSV0_1.remove(storage, pack_id);
+    }
+    for (pack_id, _) in token_packs.iter() {
+        ALLTOKENPACKSV0_1.remove(storage, pack_id);
+    }
+    for ((pack_id, owner), _) in royalty_fees.iter() {
+        ROYALTYFEESV0_1.remove(storage, (pack_id, owner));
+    }
+    for ((pack_id, owner), _) in token_royalty_fees.iter() {
+        TOKENROYALTYFEESV0_1.remove(storage, (pack_id, owner));
+    }
+
+    for (_, pack) in nft_packs {
+        all_nft_packs().save(storage, pack.pack_id, &pack)?;
+    }
+    for (_, pack) in token_packs {
+        all_token_packs().save(storage, pack.pack_id, &pack)?;
+    }
+    for ((pack_id, owner), royalty_fee) in royalty_fees {
+        ROYALTYFEES.save(storage, (parse_pack_id(&pack_id)?, &owner), &royalty_fee)?;
+    }
+    for ((pack_id, owner), royalty_fee) in token_royalty_fees {
+        TOKENROYALTYFEES.save(storage, (parse_pack_id(&pack_id)?, &owner), &royalty_fee)?;
+    }
+
+    for balances in [NFTPACKBALANCESV0_1, TOKENPACKBALANCESV0_1] {
+        let owners = balances
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<String>>>()?;
+        for owner in owners {
+            balances.remove(storage, &owner);
+        }
+    }
+    Ok(())
+}
+
+fn parse_pack_id(key: &str) -> StdResult<u64> {
+    key.parse::<u64>()
+        .map_err(|_| StdError::generic_err(format!("Invalid pack id key: {}", key)))
+}
+
+/// rekeys a name registry by `name_key`, names released with a `false` flag are dropped
+fn migrate_name_registry(storage: &mut dyn Storage, registry: Map<&str, bool>) -> StdResult<()> {
+    let names = registry
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, bool)>>>()?;
+    for (name, taken) in names {
+        registry.remove(storage, &name);
+        if taken {
+            registry.save(storage, &name_key(&name), &true)?;
+        }
+    }
+    Ok(())
+}
+
+/// parses a `major.minor.patch` version, ignoring any pre-release suffix
+fn parse_version(version: &str) -> StdResult<(u64, u64, u64)> {
+    let invalid = || StdError::generic_err(format!("Invalid contract version: {}", version));
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let parts = core
+        .split('.')
+        .map(|p| p.parse::<u64>().map_err(|_| invalid()))
+        .collect::<StdResult<Vec<u64>>>()?;
+    match parts.as_slice() {
+        [major, minor, patch] => Ok((*major, *minor, *patch)),
+        _ => Err(invalid()),
+    }
+}
@@ -4,6 +4,10 @@ use serde::{Deserialize, Serialize};
 use cosmwasm_std::{ Binary, Uint128, Decimal };
 use cw721::Expiration;
 
+use crate::asset::Asset;
+use crate::metadata::Trait;
+use crate::state::{MintPhase, NftPack, TokenPack, PackMetadata, PackMetadataEditor, PauseState, SaleRecord, SaleStats};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     /// Name of the NFT contract
@@ -11,12 +15,36 @@ pub struct InstantiateMsg {
     /// Symbol of the NFT contract
     pub symbol: String,
 
-    /// The minter is the only one who can create new NFTs.
-    /// This is designed for a base NFT that is controlled by an external program
-    /// or contract. You will likely replace this with custom logic in custom NFTs
+    /// The initial minter, also the contract owner.
+    /// The owner can grant the minter role to more accounts with `AddMinter`
     pub minter: String,
+
+    /// Maximum number of packable NFTs in the collection, 5000 if unset
+    pub max_packable_nft: Option<u64>,
+    /// Maximum number of NFTs in a single pack, 10 if unset
+    pub max_pack_item_count: Option<u64>,
+    /// Maximum number of owners in a pack's royalty chain, 10 if unset
+    pub max_royalty_owner: Option<u64>,
+    /// Fee charged on buy/sell, zero if unset
+    pub buy_sell_fee: Option<Decimal>,
+    /// Receiver of the buy/sell fee, the minter if unset
+    pub fee_collector: Option<String>,
+    /// Account allowed to pause operations, the minter if unset
+    pub pauser: Option<String>,
+    /// Who may edit NFT pack metadata, the pack owner if unset
+    pub pack_metadata_editor: Option<PackMetadataEditor>,
+    /// Deduct the Terra stability tax from native payouts, true if unset
+    pub deduct_tax: Option<bool>,
+    /// Denom of pack prices, uusd if unset
+    pub sale_denom: Option<String>,
+    /// Upper bound of royalty rates, if unset the highest rate at which a full royalty chain
+    /// and the buy/sell fee fit the price
+    pub max_royalty_fee: Option<Decimal>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 /// This is like Cw721ExecuteMsg but we add a Mint command for an owner
 /// to make this stand-alone. You will likely want to remove mint and
 /// use other control logic in any contract that inherits this.
@@ -50,30 +78,156 @@ pub enum ExecuteMsg<T> {
     /// Remove previously granted ApproveAll permission
     RevokeAll { operator: String },
 
-    /// Mint a new NFT, can only be called by the contract minter
+    /// Mint a new NFT, can only be called by a minter
     MintPackable(MintMsg<T>),
+    /// Mint several NFTs at once, every entry is validated before anything is minted
+    BatchMintPackable { mints: Vec<MintMsg<T>> },
+    /// Mint NFTs straight into a new pack owned by the minter
+    MintPack {
+        pack_name: String,
+        items: Vec<MintMsg<T>>,
+        price: Uint128,
+        royalty_fee: Decimal,
+        metadata: Option<PackMetadata>,
+    },
+    /// Grant the minter role or change its quota, can only be called by the contract owner
+    AddMinter { minter: String, quota: Option<u64> },
+    /// Revoke the minter role, can only be called by the contract owner
+    RemoveMinter { minter: String },
+    /// Give up the sender's own minter role
+    RenounceMinter {},
+    /// Register metadata for public minting, can only be called by the contract owner.
+    /// The `owner` of each entry is replaced by the buyer. During an unrevealed hidden drop the entries
+    /// take its next indices and their `token_uri` is dropped
+    EnqueueMints { mints: Vec<MintMsg<T>> },
+    /// Open a public mint phase, can only be called by the contract owner
+    AddMintPhase {
+        start: Expiration,
+        end: Expiration,
+        price: Asset,
+        per_wallet_limit: Option<u64>,
+        /// Restrict the phase to these addresses when set
+        allowlist: Option<Vec<String>>,
+        /// Restrict the phase to a merkle allowlist when set, hex encoded
+        merkle_root: Option<String>,
+    },
+    /// Close a public mint phase, can only be called by the contract owner
+    RemoveMintPhase { phase_id: u64 },
+    /// Set how public mint proceeds are split, can only be called by the contract owner.
+    /// Creators get their share and the contract owner gets the rest
+    SetCreatorShares { shares: Vec<CreatorShareMsg> },
+    /// Mint the next `drop_size` NFTs behind `placeholder_uri`, can only be called by the contract owner.
+    /// `seed_commitment` is the hex encoded sha256 of the seed followed by the base URI,
+    /// both later given to `Reveal`
+    StartHiddenDrop { placeholder_uri: String, seed_commitment: String, drop_size: u64 },
+    /// Reveal the hidden drop and close it to new mints, can only be called by the contract owner.
+    /// The offset mixes the seed with the reveal block
+    Reveal { base_uri: String, seed: String },
+    /// Write the revealed URIs of hidden tokens, can be called by anyone.
+    /// A token whose revealed URI is already taken keeps the placeholder as a reveal conflict
+    RevealTokens { start_after: Option<String>, limit: Option<u32> },
+    /// Give a token left in a reveal conflict an unused URI, can only be called by the contract owner
+    ResolveRevealConflict { token_id: String, token_uri: String },
+    /// Pay the phase price and mint the next registered NFT.
+    /// A cw20 price is collected with `TransferFrom`, so the sender has to increase the allowance first
+    PublicMint { phase_id: u64, claim: Option<MerkleClaim> },
 
     /// Burn an NFT the sender has access to
     BurnPackable { token_id: String },
 
-    PackNfts { token_ids: Vec<String>, pack_name: String, price: Uint128, royalty_fee: Decimal },
+    PackNfts {
+        token_ids: Vec<String>,
+        pack_name: String,
+        price: Uint128,
+        royalty_fee: Decimal,
+        metadata: Option<PackMetadata>,
+    },
+    /// Rename a pack, only the pack owner can execute this
+    RenamePack { kind: PackKind, pack_id: u64, name: String },
+    /// Replace the metadata of an NFT pack, allowed per the configured `PackMetadataEditor`
+    UpdateNftPackMetadata { pack_id: u64, metadata: PackMetadata },
+    /// Replace the metadata of a token pack, allowed per the configured `PackMetadataEditor`
+    UpdateTokenPackMetadata { pack_id: u64, metadata: PackMetadata },
     UnpackNfts { pack_id: u64 },
+    /// Add NFTs of the sender to their unlisted pack
+    AddToNftPack { pack_id: u64, token_ids: Vec<String> },
+    /// Take NFTs out of the sender's unlisted pack
+    RemoveFromNftPack { pack_id: u64, token_ids: Vec<String> },
     ApproveNftPack { to: String, pack_id: u64 },
+    /// Move an NFT pack approved to this contract, a new owner joins the royalty chain at a zero rate
     TransferNftPack { from: String, to: String, pack_id: u64 },
-    PackTokens { pack_name: String, token_address: String, amount: Uint128, price: Uint128, royalty_fee: Decimal },
+    PackTokens {
+        pack_name: String,
+        token_address: String,
+        amount: Uint128,
+        price: Uint128,
+        royalty_fee: Decimal,
+        metadata: Option<PackMetadata>,
+    },
     UnpackTokens { pack_id: u64 },
     ApproveTokenPack { pack_id: u64, to: String },
     TransferTokenPack { pack_id: u64, from: String, to: String },
-    BuyTokenPack { pack_id: u64 },
-    SetBuyCellFee { fee: Decimal },
+    /// Lower the sender's royalty rate on a pack, the position in the royalty chain is kept
+    UpdateRoyaltyFee { kind: PackKind, pack_id: u64, royalty_fee: Decimal },
+    /// Drop the sender's royalty rate on a pack to zero, the position in the royalty chain is kept
+    RenounceRoyalty { kind: PackKind, pack_id: u64 },
+    /// Pay the sender's royalties on a pack to another address, or back to the sender if unset
+    SetRoyaltyRecipient { kind: PackKind, pack_id: u64, recipient: Option<String> },
+    /// Buy a listed NFT pack at its price in the sale denom, the pack moves to the sender.
+    /// A new owner joins the royalty chain at `royalty_fee`, zero if unset
+    BuyNftPack { pack_id: u64, royalty_fee: Option<Decimal> },
+    /// Buy a listed token pack like `BuyNftPack`
+    BuyTokenPack { pack_id: u64, royalty_fee: Option<Decimal> },
+    /// Update any config value, can only be called by the contract owner.
+    /// The buy/sell fee has to leave room for the royalty rates of every existing chain
+    UpdateConfig {
+        buy_sell_fee: Option<Decimal>,
+        fee_collector: Option<String>,
+        max_packable_nft: Option<u64>,
+        max_pack_item_count: Option<u64>,
+        max_royalty_owner: Option<u64>,
+        pack_metadata_editor: Option<PackMetadataEditor>,
+        deduct_tax: Option<bool>,
+        sale_denom: Option<String>,
+        max_royalty_fee: Option<Decimal>,
+    },
+    /// Propose a new contract owner, who has to accept it before the expiration
+    ProposeNewOwner { owner: String, expires: Option<Expiration> },
+    /// Accept a pending ownership proposal, can only be called by the proposed owner
+    AcceptOwnership {},
+    /// Change the pauser, can only be called by the contract owner
+    SetPauser { pauser: String },
+    /// Pause or resume operations, can only be called by the pauser.
+    /// Unset flags are left unchanged
+    SetPauseState {
+        minting: Option<bool>,
+        packing: Option<bool>,
+        trading: Option<bool>,
+        transfers: Option<bool>,
+    },
     SetTokenPrice { token_id: String, price: Uint128 },
     SetNftPackPrice { pack_id: u64, price: Uint128 },
     SetTokenPackPrice { pack_id: u64, price: Uint128 },
+    /// NFTs are only sold within packs, this can only take an NFT off sale
     SetTokenForSale { token_id: String, for_sale: bool },
     SetNftPackForSale { pack_id: u64, for_sale: bool },
     SetTokenPackForSale { pack_id: u64, for_sale: bool },
 }
 
+/// Proof that the sender is in a merkle allowlist with `allowance` mints
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MerkleClaim {
+    pub allowance: u64,
+    /// Hex encoded sibling hashes from the leaf up to the root
+    pub proof: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CreatorShareMsg {
+    pub address: String,
+    pub share: Decimal,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MintMsg<T> {
     /// Unique ID of the NFT
@@ -88,7 +242,9 @@ pub struct MintMsg<T> {
     pub extension: T,
     
     pub name: String,
-    pub price: Uint128
+    pub price: Uint128,
+    /// Share of each sale paid to the minter, reported through cw2981. No royalty if unset
+    pub royalty_fee: Option<Decimal>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -163,8 +319,334 @@ pub enum QueryMsg {
         limit: Option<u32>,
     },
 
-    // Return the minter
+    /// Deprecated, kept for cw721 clients: returns one current minter, use `Minters` to list them all
     Minter {},
+    /// List all minters with their quotas: `MintersResponse`
+    Minters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Return the contract config: `Config`
+    Config {},
+    /// Return the pending ownership proposal if any: `Option<OwnershipProposal>`
+    OwnershipProposal {},
+    /// Return the pauser and the paused operations: `PauseStateResponse`
+    PauseState {},
+    /// List the public mint phases: `MintPhasesResponse`
+    MintPhases {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Return how many NFTs an address minted in a phase and if it is allowlisted: `MintPhaseStatusResponse`
+    MintPhaseStatus { phase_id: u64, address: String },
+    /// Return the number of registered NFTs left to mint: `MintQueueResponse`
+    MintQueue {},
+    /// Return the public mint proceeds split: `Vec<CreatorShare>`
+    CreatorShares {},
+    /// Return the current hidden drop if any: `Option<HiddenDrop>`
+    HiddenDrop {},
+    /// Return whether each token is revealed: `RevealedResponse`
+    Revealed { token_ids: Vec<String> },
+    /// List the tokens waiting for `ResolveRevealConflict`: `RevealConflictsResponse`
+    RevealConflicts { start_after: Option<String>, limit: Option<u32> },
+    /// Return the items of an NFT pack from the rarest, with their attributes: `PackRarityResponse`.
+    /// An item scores `item_count / items sharing the trait` for each of its traits
+    RarestInPack { pack_id: u64, limit: Option<u32> },
+    /// Return an NFT pack with its metadata: `NftPack`
+    NftPack { pack_id: u64 },
+    /// Return the NFT packs carrying a tag, ordered by id: `NftPacksResponse`
+    NftPacksByTag {
+        tag: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Return the token packs carrying a tag, ordered by id: `TokenPacksResponse`
+    TokenPacksByTag {
+        tag: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// List the NFT packs, ordered by id: `NftPacksResponse`
+    AllNftPacks {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// List the token packs, ordered by id: `TokenPacksResponse`
+    AllTokenPacks {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Return the NFT packs owned by an address, ordered by id: `NftPacksResponse`
+    NftPacksByOwner {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Return the NFT packs opened by an address, ordered by id: `NftPacksResponse`
+    NftPacksByMinter {
+        minter: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Return the token packs owned by an address, ordered by id: `TokenPacksResponse`
+    TokenPacksByOwner {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Return the token packs opened by an address, ordered by id: `TokenPacksResponse`
+    TokenPacksByMinter {
+        minter: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Return how many packs of a kind an address owns: `PackBalanceResponse`
+    PackBalance { kind: PackKind, owner: String },
+    /// cw2981 royalty owed to the minter of a packable NFT: `RoyaltiesInfoResponse`
+    RoyaltyInfo { token_id: String, sale_price: Uint128 },
+    /// cw2981 signal that this contract implements royalties: `CheckRoyaltiesResponse`
+    CheckRoyalties {},
+    /// Return the multi-level royalty split of a pack in chain order: `PackRoyaltiesResponse`.
+    /// Amounts are computed for `sale_price` if given, royalties only apply to the gain over the previous price
+    PackRoyalties {
+        kind: PackKind,
+        pack_id: u64,
+        sale_price: Option<Uint128>,
+    },
+    /// Return how a sale of `item` at `price` would be split, as a purchase pays it out: `SimulateSaleResponse`.
+    /// Fails for single NFTs, they are only sold within packs
+    SimulateSale { item: SaleItem, price: Asset },
+    /// Return whether a name is free in a registry, regardless of case: `NameAvailableResponse`
+    NameAvailable { kind: NameKind, name: String },
+    /// Return the sales of an item, oldest first: `SaleHistoryResponse`
+    SaleHistory {
+        item: SaleItem,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Return the sales an address bought or sold in, oldest first: `SaleHistoryResponse`
+    SaleHistoryByAddress {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Return the sale volume and prices of the collection per price asset: `SaleStatsResponse`
+    SaleStats {},
+    /// Return the collection aggregates: `CollectionStatsResponse`
+    CollectionStats {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum PackKind {
+    Nft,
+    Token,
+}
+
+/// Registries of unique names
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum NameKind {
+    Nft,
+    NftPack,
+    TokenPack,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PackRoyalty {
+    pub address: String,
+    /// receives the royalties of `address`
+    pub recipient: String,
+    pub royalty_fee: Decimal,
+    pub royalty_amount: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PackRoyaltiesResponse {
+    pub pack_id: u64,
+    pub royalties: Vec<PackRoyalty>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SaleItem {
+    Nft { token_id: String },
+    NftPack { pack_id: u64 },
+    TokenPack { pack_id: u64 },
+}
+
+impl SaleItem {
+    pub fn item_type(&self) -> &'static str {
+        match self {
+            SaleItem::Nft { .. } => "nft",
+            SaleItem::NftPack { .. } => "nft_pack",
+            SaleItem::TokenPack { .. } => "token_pack",
+        }
+    }
+
+    pub fn item_id(&self) -> String {
+        match self {
+            SaleItem::Nft { token_id } => token_id.clone(),
+            SaleItem::NftPack { pack_id } | SaleItem::TokenPack { pack_id } => pack_id.to_string(),
+        }
+    }
+
+    pub fn pack(kind: PackKind, pack_id: u64) -> Self {
+        match kind {
+            PackKind::Nft => SaleItem::NftPack { pack_id },
+            PackKind::Token => SaleItem::TokenPack { pack_id },
+        }
+    }
+
+    /// storage key of the item, unique across item types
+    pub fn key(&self) -> String {
+        format!("{}/{}", self.item_type(), self.item_id())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RoyaltyPayment {
+    pub address: String,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SimulateSaleResponse {
+    pub price: Asset,
+    /// Platform fee paid to the fee collector.
+    /// All amounts are what the recipients receive, net of `tax`
+    pub fee: Uint128,
+    /// Royalties in chain order
+    pub royalties: Vec<RoyaltyPayment>,
+    /// Stability tax of every native payout
+    pub tax: Uint128,
+    pub seller: String,
+    /// What the seller receives after fee and royalties
+    pub seller_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SaleHistoryResponse {
+    pub sales: Vec<SaleRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SaleStatsResponse {
+    pub stats: Vec<SaleStats>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CollectionStatsResponse {
+    /// packable NFTs minted and not burned
+    pub token_count: u64,
+    pub nft_packs: u64,
+    pub token_packs: u64,
+    /// packs for sale, NFTs are only sold within packs
+    pub items_listed: u64,
+    /// lowest listing price per denom, an item keeps the sale denom it was listed in
+    pub floor_prices: Vec<Asset>,
+    pub volume: Vec<Asset>,
+    /// royalties paid out per asset, net of tax
+    pub royalties: Vec<Asset>,
+    /// addresses holding an NFT or a pack
+    pub unique_holders: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct NameAvailableResponse {
+    pub name: String,
+    pub available: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct NftPacksResponse {
+    pub packs: Vec<NftPack>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TokenPacksResponse {
+    pub packs: Vec<TokenPack>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PackBalanceResponse {
+    pub balance: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PauseStateResponse {
+    pub pauser: String,
+    pub state: PauseState,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MintPhaseResponse {
+    pub phase_id: u64,
+    pub phase: MintPhase,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MintPhasesResponse {
+    pub phases: Vec<MintPhaseResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MintPhaseStatusResponse {
+    pub minted: u64,
+    pub allowlisted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MintQueueResponse {
+    pub remaining: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RevealedToken {
+    pub token_id: String,
+    pub revealed: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RevealedResponse {
+    pub tokens: Vec<RevealedToken>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RevealConflict {
+    pub token_id: String,
+    /// the revealed URI another token already had
+    pub token_uri: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RevealConflictsResponse {
+    pub conflicts: Vec<RevealConflict>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ItemRarity {
+    pub token_id: String,
+    pub score: Decimal,
+    pub attributes: Vec<Trait>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PackRarityResponse {
+    pub pack_id: u64,
+    pub items: Vec<ItemRarity>,
 }
 
 /// Shows who can mint these tokens
@@ -172,3 +654,15 @@ pub enum QueryMsg {
 pub struct MinterResponse {
     pub minter: String,
 }
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MinterInfoResponse {
+    pub minter: String,
+    pub quota: Option<u64>,
+    pub minted: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MintersResponse {
+    pub minters: Vec<MinterInfoResponse>,
+}
@@ -1,18 +1,33 @@
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use cosmwasm_std::{to_binary, Addr, Binary, BlockInfo, Deps, Env, Order, StdError, StdResult};
+use cosmwasm_std::{
+    to_binary, Addr, Binary, BlockInfo, Decimal, Deps, Env, Order, StdError, StdResult, Uint128,
+};
 
 use cw721::{
     AllNftInfoResponse, ApprovalResponse, ApprovalsResponse, ContractInfoResponse, CustomMsg,
     Cw721Query, Expiration, NftInfoResponse, NumTokensResponse, OperatorsResponse, OwnerOfResponse,
     TokensResponse,
 };
-use cw_storage_plus::Bound;
+use cw_storage_plus::{Bound, MultiIndex};
 use cw_utils::maybe_addr;
 
-use crate::msg::{MinterResponse, QueryMsg};
-use crate::state::{Approval, Cw721Contract, TokenInfo};
+use crate::msg::{
+    MintPhaseResponse, MintPhaseStatusResponse, MintPhasesResponse, MintQueueResponse,
+    MinterInfoResponse, MinterResponse, MintersResponse, PauseStateResponse, QueryMsg,
+    CheckRoyaltiesResponse, ItemRarity, NameAvailableResponse, NameKind, NftPacksResponse, PackKind,
+    PackRoyaltiesResponse, PackRoyalty, RoyaltiesInfoResponse, CollectionStatsResponse, PackBalanceResponse, TokenPacksResponse, SaleHistoryResponse, SaleItem, SaleStatsResponse, SimulateSaleResponse, PackRarityResponse, RevealedResponse, RevealConflict, RevealConflictsResponse, RevealedToken,
+};
+use crate::asset::Asset;
+use crate::metadata::HasAttributes;
+use crate::settlement::Settlement;
+use crate::state::{
+    Approval, Config, CreatorShare, Cw721Contract, HiddenDrop, NftPack, OwnershipProposal, TokenInfo, CONFIG,
+    all_nft_packs, ALLPACKABLENFTS, all_token_packs, royalty_maps, royalty_recipient, CREATORSHARES, PACKTAGS, TOKENPACKTAGS, PACKNAMEEXISTS, TOKENNAMEEXISTS, TOKENPACKNAMEEXISTS, name_key, HIDDENDROP, HIDDENTOKENS, REVEALCONFLICTS, TOKENURIEXISTS, MINTERS, MINTPHASEALLOWLIST, MINTPHASEMINTED, MINTPHASES, MINTQUEUEHEAD,
+    MINTQUEUETAIL, OWNERSHIPPROPOSAL, PAUSER, PAUSESTATE, ADDRESSSALES, ITEMSALES, SALES, SALESTATS,
+    COLLECTIONCOUNTERS, LISTEDDENOMS, LISTEDPRICES, LISTINGS,
+};
 
 const DEFAULT_LIMIT: u32 = 10;
 const MAX_LIMIT: u32 = 30;
@@ -34,7 +49,7 @@ where
     fn nft_info(&self, deps: Deps, token_id: String) -> StdResult<NftInfoResponse<T>> {
         let info = self.tokens.load(deps.storage, &token_id)?;
         Ok(NftInfoResponse {
-            token_uri: Some(info.token_uri),
+            token_uri: Some(self.resolve_token_uri(deps, &token_id, info.token_uri)?),
             extension: info.extension,
         })
     }
@@ -199,7 +214,7 @@ where
                 approvals: humanize_approvals(&env.block, &info, include_expired),
             },
             info: NftInfoResponse {
-                token_uri: Some(info.token_uri),
+                token_uri: Some(self.resolve_token_uri(deps, &token_id, info.token_uri.clone())?),
                 extension: info.extension,
             },
         })
@@ -211,16 +226,511 @@ where
     T: Serialize + DeserializeOwned + Clone,
     C: CustomMsg,
 {
+    /// the instantiating minter while it keeps the role, otherwise the first one of `MINTERS`
     pub fn minter(&self, deps: Deps) -> StdResult<MinterResponse> {
         let minter_addr = self.minter.load(deps.storage)?;
+        let minter_addr = if MINTERS.has(deps.storage, &minter_addr) {
+            minter_addr
+        } else {
+            MINTERS
+                .keys(deps.storage, None, None, Order::Ascending)
+                .next()
+                .transpose()?
+                .ok_or_else(|| StdError::not_found("minter"))?
+        };
         Ok(MinterResponse {
             minter: minter_addr.to_string(),
         })
     }
 
+    pub fn minters(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<MintersResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start_addr = maybe_addr(deps.api, start_after)?;
+        let start = start_addr.as_ref().map(Bound::exclusive);
+
+        let minters = MINTERS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(minter, info)| MinterInfoResponse {
+                    minter: minter.to_string(),
+                    quota: info.quota,
+                    minted: info.minted,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(MintersResponse { minters })
+    }
+
+    pub fn config(&self, deps: Deps) -> StdResult<Config> {
+        CONFIG.load(deps.storage)
+    }
+
+    pub fn ownership_proposal(&self, deps: Deps) -> StdResult<Option<OwnershipProposal>> {
+        OWNERSHIPPROPOSAL.may_load(deps.storage)
+    }
+
+    pub fn pause_state(&self, deps: Deps) -> StdResult<PauseStateResponse> {
+        Ok(PauseStateResponse {
+            pauser: PAUSER.load(deps.storage)?.to_string(),
+            state: PAUSESTATE.may_load(deps.storage)?.unwrap_or_default(),
+        })
+    }
+
+    pub fn mint_phases(
+        &self,
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<MintPhasesResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let phases = MINTPHASES
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(phase_id, phase)| MintPhaseResponse { phase_id, phase }))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(MintPhasesResponse { phases })
+    }
+
+    pub fn mint_phase_status(
+        &self,
+        deps: Deps,
+        phase_id: u64,
+        address: String,
+    ) -> StdResult<MintPhaseStatusResponse> {
+        let addr = deps.api.addr_validate(&address)?;
+        Ok(MintPhaseStatusResponse {
+            minted: MINTPHASEMINTED
+                .may_load(deps.storage, (phase_id, &addr))?
+                .unwrap_or_default(),
+            allowlisted: MINTPHASEALLOWLIST.has(deps.storage, (phase_id, &addr)),
+        })
+    }
+
+    pub fn mint_queue(&self, deps: Deps) -> StdResult<MintQueueResponse> {
+        let head = MINTQUEUEHEAD.may_load(deps.storage)?.unwrap_or_default();
+        let tail = MINTQUEUETAIL.may_load(deps.storage)?.unwrap_or_default();
+        Ok(MintQueueResponse {
+            remaining: tail - head,
+        })
+    }
+
+    pub fn creator_shares(&self, deps: Deps) -> StdResult<Vec<CreatorShare>> {
+        Ok(CREATORSHARES.may_load(deps.storage)?.unwrap_or_default())
+    }
+
+    /// revealed tokens not written by `RevealTokens` yet still store the placeholder
+    pub fn resolve_token_uri(&self, deps: Deps, token_id: &str, token_uri: String) -> StdResult<String> {
+        Ok(self.pending_reveal_uri(deps, token_id)?.unwrap_or(token_uri))
+    }
+
+    /// the URI `RevealTokens` will write for a token of a revealed drop, `None` if it would conflict
+    fn pending_reveal_uri(&self, deps: Deps, token_id: &str) -> StdResult<Option<String>> {
+        let index = match HIDDENTOKENS.may_load(deps.storage, token_id)? {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        Ok(HIDDENDROP
+            .may_load(deps.storage)?
+            .and_then(|drop| drop.revealed_uri(index))
+            .filter(|uri| !TOKENURIEXISTS.has(deps.storage, uri)))
+    }
+
+    pub fn hidden_drop(&self, deps: Deps) -> StdResult<Option<HiddenDrop>> {
+        HIDDENDROP.may_load(deps.storage)
+    }
+
+    pub fn revealed(&self, deps: Deps, token_ids: Vec<String>) -> StdResult<RevealedResponse> {
+        let tokens = token_ids
+            .into_iter()
+            .map(|token_id| {
+                self.tokens.load(deps.storage, &token_id)?;
+                let revealed = if HIDDENTOKENS.has(deps.storage, &token_id) {
+                    self.pending_reveal_uri(deps, &token_id)?.is_some()
+                } else {
+                    !REVEALCONFLICTS.has(deps.storage, &token_id)
+                };
+                Ok(RevealedToken { token_id, revealed })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(RevealedResponse { tokens })
+    }
+
+    pub fn reveal_conflicts(
+        &self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<RevealConflictsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+        let conflicts = REVEALCONFLICTS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(token_id, token_uri)| RevealConflict { token_id, token_uri }))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(RevealConflictsResponse { conflicts })
+    }
+
+    pub fn nft_pack(&self, deps: Deps, pack_id: u64) -> StdResult<NftPack> {
+        all_nft_packs().load(deps.storage, pack_id)
+    }
+
+    pub fn nft_packs_by_tag(
+        &self,
+        deps: Deps,
+        tag: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<NftPacksResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let tag = tag.trim().to_lowercase();
+
+        let packs = PACKTAGS
+            .prefix(&tag)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|pack_id| all_nft_packs().load(deps.storage, pack_id?))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(NftPacksResponse { packs })
+    }
+
+    pub fn token_packs_by_tag(
+        &self,
+        deps: Deps,
+        tag: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<TokenPacksResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let tag = tag.trim().to_lowercase();
+
+        let packs = TOKENPACKTAGS
+            .prefix(&tag)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|pack_id| all_token_packs().load(deps.storage, pack_id?))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(TokenPacksResponse { packs })
+    }
+
+    pub fn all_nft_packs(&self, deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<NftPacksResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let packs = all_nft_packs()
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, pack)| pack))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(NftPacksResponse { packs })
+    }
+
+    pub fn all_token_packs(&self, deps: Deps, start_after: Option<u64>, limit: Option<u32>) -> StdResult<TokenPacksResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let packs = all_token_packs()
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, pack)| pack))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(TokenPacksResponse { packs })
+    }
+
+    pub fn pack_balance(&self, deps: Deps, kind: PackKind, owner: String) -> StdResult<PackBalanceResponse> {
+        let owner = deps.api.addr_validate(&owner)?;
+        let balance = match kind {
+            PackKind::Nft => all_nft_packs()
+                .idx
+                .current_owner
+                .prefix(owner)
+                .keys_raw(deps.storage, None, None, Order::Ascending)
+                .count(),
+            PackKind::Token => all_token_packs()
+                .idx
+                .current_owner
+                .prefix(owner)
+                .keys_raw(deps.storage, None, None, Order::Ascending)
+                .count(),
+        };
+        Ok(PackBalanceResponse { balance: balance as u64 })
+    }
+
+    pub fn sale_history(
+        &self,
+        deps: Deps,
+        item: SaleItem,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<SaleHistoryResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+
+        let sales = ITEMSALES
+            .prefix(&item.key())
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|sale_id| SALES.load(deps.storage, sale_id?))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(SaleHistoryResponse { sales })
+    }
+
+    pub fn sale_history_by_address(
+        &self,
+        deps: Deps,
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<SaleHistoryResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let address = deps.api.addr_validate(&address)?;
+
+        let sales = ADDRESSSALES
+            .prefix(&address)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|sale_id| SALES.load(deps.storage, sale_id?))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(SaleHistoryResponse { sales })
+    }
+
+    pub fn collection_stats(&self, deps: Deps) -> StdResult<CollectionStatsResponse> {
+        let counters = COLLECTIONCOUNTERS.may_load(deps.storage)?.unwrap_or_default();
+        let floor_prices = LISTEDDENOMS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|denom| {
+                let (_, key) = LISTEDPRICES
+                    .sub_prefix(&denom?)
+                    .keys(deps.storage, None, None, Order::Ascending)
+                    .next()
+                    .ok_or_else(|| StdError::not_found("listing"))??;
+                LISTINGS.load(deps.storage, &key)
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        let stats = self.sale_stats(deps)?.stats;
+        Ok(CollectionStatsResponse {
+            token_count: self.token_count(deps.storage)?,
+            nft_packs: counters.nft_packs,
+            token_packs: counters.token_packs,
+            items_listed: counters.items_listed,
+            floor_prices,
+            volume: stats.iter().map(|s| Asset { info: s.asset.clone(), amount: s.volume }).collect(),
+            royalties: stats.iter().map(|s| Asset { info: s.asset.clone(), amount: s.royalties }).collect(),
+            unique_holders: counters.unique_holders,
+        })
+    }
+
+    pub fn sale_stats(&self, deps: Deps) -> StdResult<SaleStatsResponse> {
+        let stats = SALESTATS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, stats)| stats))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(SaleStatsResponse { stats })
+    }
+
+    pub fn royalty_info(
+        &self,
+        deps: Deps,
+        token_id: String,
+        sale_price: Uint128,
+    ) -> StdResult<RoyaltiesInfoResponse> {
+        let packable_token = ALLPACKABLENFTS.load(deps.storage, &token_id)?;
+        Ok(RoyaltiesInfoResponse {
+            address: packable_token.minted_by.to_string(),
+            royalty_amount: sale_price * packable_token.royalty_fee,
+        })
+    }
+
+    pub fn check_royalties(&self) -> StdResult<CheckRoyaltiesResponse> {
+        Ok(CheckRoyaltiesResponse { royalty_payments: true })
+    }
+
+    pub fn pack_royalties(
+        &self,
+        deps: Deps,
+        kind: PackKind,
+        pack_id: u64,
+        sale_price: Option<Uint128>,
+    ) -> StdResult<PackRoyaltiesResponse> {
+        let (royalty_owners, previous_price) = match kind {
+            PackKind::Nft => {
+                let pack = all_nft_packs().load(deps.storage, pack_id)?;
+                (pack.royalty_owners, pack.previous_price)
+            }
+            PackKind::Token => {
+                let pack = all_token_packs().load(deps.storage, pack_id)?;
+                (pack.royalty_owners, pack.previous_price)
+            }
+        };
+        let (fees, _) = royalty_maps(kind);
+        let gain = sale_price.map(|price| price.saturating_sub(previous_price));
+        let royalties = royalty_owners
+            .into_iter()
+            .map(|owner| {
+                let royalty_fee = fees.load(deps.storage, (pack_id, owner.as_str()))?;
+                let recipient = royalty_recipient(deps.storage, kind, pack_id, &owner)?;
+                Ok(PackRoyalty {
+                    address: owner.to_string(),
+                    recipient: recipient.to_string(),
+                    royalty_fee,
+                    royalty_amount: gain.map(|gain| gain * royalty_fee),
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(PackRoyaltiesResponse { pack_id, royalties })
+    }
+
+    pub fn simulate_sale(&self, deps: Deps, item: SaleItem, price: Asset) -> StdResult<SimulateSaleResponse> {
+        let settlement = Settlement::new(deps, &item, price)
+            .map_err(|err| StdError::generic_err(err.to_string()))?;
+        Ok(settlement.into())
+    }
+
+    pub fn name_available(&self, deps: Deps, kind: NameKind, name: String) -> StdResult<NameAvailableResponse> {
+        let registry = match kind {
+            NameKind::Nft => TOKENNAMEEXISTS,
+            NameKind::NftPack => PACKNAMEEXISTS,
+            NameKind::TokenPack => TOKENPACKNAMEEXISTS,
+        };
+        let available = !registry.has(deps.storage, &name_key(&name));
+        Ok(NameAvailableResponse { name, available })
+    }
+}
+
+impl<'a, T, C> Cw721Contract<'a, T, C>
+where
+    T: Serialize + DeserializeOwned + Clone + HasAttributes,
+    C: CustomMsg,
+{
+    pub fn rarest_in_pack(
+        &self,
+        deps: Deps,
+        pack_id: u64,
+        limit: Option<u32>,
+    ) -> StdResult<PackRarityResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let pack = all_nft_packs().load(deps.storage, pack_id)?;
+        let attributes = pack
+            .pack_items
+            .iter()
+            .map(|token_id| Ok(self.tokens.load(deps.storage, token_id)?.extension.attributes()))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let item_count = pack.pack_items.len() as u128;
+        let mut items: Vec<ItemRarity> = pack
+            .pack_items
+            .iter()
+            .zip(attributes.iter())
+            .map(|(token_id, traits)| {
+                let score = traits.iter().fold(Decimal::zero(), |score, t| {
+                    let sharing = attributes.iter().filter(|other| other.contains(t)).count() as u128;
+                    score + Decimal::from_ratio(item_count, sharing)
+                });
+                ItemRarity {
+                    token_id: token_id.clone(),
+                    score,
+                    attributes: traits.clone(),
+                }
+            })
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(item.score));
+        items.truncate(limit);
+        Ok(PackRarityResponse { pack_id, items })
+    }
+
+    /// `query` extended with `RarestInPack`, which reads the traits of the token extension
+    pub fn query_with_rarity(&self, deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        match msg {
+            QueryMsg::RarestInPack { pack_id, limit } => {
+                to_binary(&self.rarest_in_pack(deps, pack_id, limit)?)
+            }
+            _ => self.query(deps, env, msg),
+        }
+    }
+}
+
+impl<'a, T, C> Cw721Contract<'a, T, C>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    C: CustomMsg,
+{
     pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         match msg {
             QueryMsg::Minter {} => to_binary(&self.minter(deps)?),
+            QueryMsg::Minters { start_after, limit } => {
+                to_binary(&self.minters(deps, start_after, limit)?)
+            }
+            QueryMsg::Config {} => to_binary(&self.config(deps)?),
+            QueryMsg::OwnershipProposal {} => to_binary(&self.ownership_proposal(deps)?),
+            QueryMsg::PauseState {} => to_binary(&self.pause_state(deps)?),
+            QueryMsg::MintPhases { start_after, limit } => {
+                to_binary(&self.mint_phases(deps, start_after, limit)?)
+            }
+            QueryMsg::MintPhaseStatus { phase_id, address } => {
+                to_binary(&self.mint_phase_status(deps, phase_id, address)?)
+            }
+            QueryMsg::MintQueue {} => to_binary(&self.mint_queue(deps)?),
+            QueryMsg::CreatorShares {} => to_binary(&self.creator_shares(deps)?),
+            QueryMsg::HiddenDrop {} => to_binary(&self.hidden_drop(deps)?),
+            QueryMsg::Revealed { token_ids } => to_binary(&self.revealed(deps, token_ids)?),
+            QueryMsg::RevealConflicts { start_after, limit } => {
+                to_binary(&self.reveal_conflicts(deps, start_after, limit)?)
+            }
+            QueryMsg::RarestInPack { .. } => Err(StdError::generic_err(
+                "RarestInPack needs a token extension with attributes, see query_with_rarity",
+            )),
+            QueryMsg::RoyaltyInfo { token_id, sale_price } => {
+                to_binary(&self.royalty_info(deps, token_id, sale_price)?)
+            }
+            QueryMsg::CheckRoyalties {} => to_binary(&self.check_royalties()?),
+            QueryMsg::PackRoyalties { kind, pack_id, sale_price } => {
+                to_binary(&self.pack_royalties(deps, kind, pack_id, sale_price)?)
+            }
+            QueryMsg::SimulateSale { item, price } => to_binary(&self.simulate_sale(deps, item, price)?),
+            QueryMsg::NameAvailable { kind, name } => to_binary(&self.name_available(deps, kind, name)?),
+            QueryMsg::NftPack { pack_id } => to_binary(&self.nft_pack(deps, pack_id)?),
+            QueryMsg::AllNftPacks { start_after, limit } => to_binary(&self.all_nft_packs(deps, start_after, limit)?),
+            QueryMsg::AllTokenPacks { start_after, limit } => to_binary(&self.all_token_packs(deps, start_after, limit)?),
+            QueryMsg::NftPacksByOwner { owner, start_after, limit } => {
+                let packs = packs_page(deps, &all_nft_packs().idx.current_owner, owner, start_after, limit)?;
+                to_binary(&NftPacksResponse { packs })
+            }
+            QueryMsg::NftPacksByMinter { minter, start_after, limit } => {
+                let packs = packs_page(deps, &all_nft_packs().idx.minted_by, minter, start_after, limit)?;
+                to_binary(&NftPacksResponse { packs })
+            }
+            QueryMsg::TokenPacksByOwner { owner, start_after, limit } => {
+                let packs = packs_page(deps, &all_token_packs().idx.current_owner, owner, start_after, limit)?;
+                to_binary(&TokenPacksResponse { packs })
+            }
+            QueryMsg::TokenPacksByMinter { minter, start_after, limit } => {
+                let packs = packs_page(deps, &all_token_packs().idx.minted_by, minter, start_after, limit)?;
+                to_binary(&TokenPacksResponse { packs })
+            }
+            QueryMsg::PackBalance { kind, owner } => to_binary(&self.pack_balance(deps, kind, owner)?),
+            QueryMsg::SaleHistory { item, start_after, limit } => {
+                to_binary(&self.sale_history(deps, item, start_after, limit)?)
+            }
+            QueryMsg::SaleHistoryByAddress { address, start_after, limit } => {
+                to_binary(&self.sale_history_by_address(deps, address, start_after, limit)?)
+            }
+            QueryMsg::SaleStats {} => to_binary(&self.sale_stats(deps)?),
+            QueryMsg::CollectionStats {} => to_binary(&self.collection_stats(deps)?),
+            QueryMsg::NftPacksByTag { tag, start_after, limit } => {
+                to_binary(&self.nft_packs_by_tag(deps, tag, start_after, limit)?)
+            }
+            QueryMsg::TokenPacksByTag { tag, start_after, limit } => {
+                to_binary(&self.token_packs_by_tag(deps, tag, start_after, limit)?)
+            }
             QueryMsg::ContractInfo {} => to_binary(&self.contract_info(deps)?),
             QueryMsg::NftInfo { token_id } => to_binary(&self.nft_info(deps, token_id)?),
             QueryMsg::OwnerOf {
@@ -306,3 +816,25 @@ fn humanize_approval(approval: &Approval) -> cw721::Approval {
         expires: approval.expires,
     }
 }
+
+/// a page of the packs an owner or minter index holds for `address`
+fn packs_page<T>(
+    deps: Deps,
+    index: &MultiIndex<Addr, T, u64>,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<T>>
+where
+    T: Serialize + DeserializeOwned + Clone,
+{
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let address = deps.api.addr_validate(&address)?;
+    index
+        .prefix(address)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, pack)| pack))
+        .collect()
+}
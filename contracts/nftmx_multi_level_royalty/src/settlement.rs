@@ -0,0 +1,368 @@
+use cosmwasm_std::{Addr, BlockInfo, CosmosMsg, Decimal, Deps, QuerierWrapper, StdError, StdResult, Storage, Uint128, Uint256};
+use std::convert::TryFrom;
+
+use crate::asset::{Asset, AssetInfo, TaxInfo};
+use crate::error::ContractError;
+use crate::msg::{PackKind, RoyaltyPayment, SaleItem, SimulateSaleResponse};
+use crate::state::{
+    Config, SaleRecord, SaleStats, all_nft_packs, all_token_packs, royalty_recipient, CONFIG, ROYALTYFEES, TOKENROYALTYFEES,
+    ADDRESSSALES, ITEMSALES, SALECOUNTER, SALES, SALESTATS, add_count,
+};
+
+/// How the price of a sale is split between the fee collector, the royalty chain and the seller.
+/// Purchases pay out a settlement and `SimulateSale` reports one, so both always agree
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Settlement {
+    pub price: Asset,
+    pub fee_collector: Addr,
+    pub fee: Uint128,
+    /// royalty recipients in chain order, redirected where an owner set a recipient
+    pub royalties: Vec<(Addr, Uint128)>,
+    pub tax: Uint128,
+    pub seller: Addr,
+    pub seller_amount: Uint128,
+}
+
+impl Settlement {
+    /// Amounts are rounded down, so the rounding dust goes to the seller
+    pub fn new(deps: Deps, item: &SaleItem, price: Asset) -> Result<Self, ContractError> {
+        let con = CONFIG.load(deps.storage)?;
+        // royalties only apply to the gain over the previous price
+        let (seller, royalty_base, royalty_fees) = match item {
+            SaleItem::Nft { .. } => return Err(ContractError::NftSoldInPacks {}),
+            SaleItem::NftPack { pack_id } => {
+                let nft_pack = all_nft_packs().load(deps.storage, *pack_id)?;
+                let royalty_fees = nft_pack
+                    .royalty_owners
+                    .into_iter()
+                    .map(|owner| {
+                        let royalty_fee = ROYALTYFEES.load(deps.storage, (*pack_id, owner.as_str()))?;
+                        Ok((royalty_recipient(deps.storage, PackKind::Nft, *pack_id, &owner)?, royalty_fee))
+                    })
+                    .collect::<StdResult<Vec<_>>>()?;
+                (nft_pack.current_owner, price.amount.saturating_sub(nft_pack.previous_price), royalty_fees)
+            }
+            SaleItem::TokenPack { pack_id } => {
+                let token_pack = all_token_packs().load(deps.storage, *pack_id)?;
+                let royalty_fees = token_pack
+                    .royalty_owners
+                    .into_iter()
+                    .map(|owner| {
+                        let royalty_fee = TOKENROYALTYFEES.load(deps.storage, (*pack_id, owner.as_str()))?;
+                        Ok((royalty_recipient(deps.storage, PackKind::Token, *pack_id, &owner)?, royalty_fee))
+                    })
+                    .collect::<StdResult<Vec<_>>>()?;
+                (token_pack.current_owner, price.amount.saturating_sub(token_pack.previous_price), royalty_fees)
+            }
+        };
+
+        let fee = share(price.amount, con.buy_sell_fee)?;
+        let royalties = royalty_fees
+            .into_iter()
+            .map(|(owner, royalty_fee)| Ok((owner, share(royalty_base, royalty_fee)?)))
+            .collect::<Result<Vec<(Addr, Uint128)>, ContractError>>()?;
+        let payouts = royalties
+            .iter()
+            .try_fold(fee, |total, (_, royalty)| total.checked_add(*royalty))
+            .map_err(|_| ContractError::SettlementOverflow {})?;
+        let seller_amount = price
+            .amount
+            .checked_sub(payouts)
+            .map_err(|_| ContractError::PayoutsExceedPrice { price: price.amount, payouts })?;
+
+        let mut net_of_tax = NetOfTax::new(&deps.querier, &con, &price.info)?;
+        let fee = net_of_tax.apply(fee)?;
+        let royalties = royalties
+            .into_iter()
+            .map(|(owner, royalty)| Ok((owner, net_of_tax.apply(royalty)?)))
+            .collect::<Result<Vec<_>, ContractError>>()?;
+        let seller_amount = net_of_tax.apply(seller_amount)?;
+        Ok(Settlement {
+            price,
+            fee_collector: con.fee_collector,
+            fee,
+            royalties,
+            tax: net_of_tax.tax,
+            seller,
+            seller_amount,
+        })
+    }
+
+    /// Append the sale to the history of the item and of both parties and to the collection stats.
+    /// Returns the sale id
+    pub fn record(&self, storage: &mut dyn Storage, block: &BlockInfo, item: &SaleItem, buyer: &Addr) -> StdResult<u64> {
+        let sale_id = add_count(SALECOUNTER.may_load(storage)?.unwrap_or_default(), 1)?;
+        SALECOUNTER.save(storage, &sale_id)?;
+        let record = SaleRecord {
+            sale_id,
+            item: item.clone(),
+            seller: self.seller.clone(),
+            buyer: buyer.clone(),
+            price: self.price.clone(),
+            fee: self.fee,
+            royalties: self.royalty_payments(),
+            tax: self.tax,
+            seller_amount: self.seller_amount,
+            block_height: block.height,
+            block_time: block.time,
+        };
+        SALES.save(storage, sale_id, &record)?;
+        ITEMSALES.save(storage, (&item.key(), sale_id), &true)?;
+        ADDRESSSALES.save(storage, (&self.seller, sale_id), &true)?;
+        ADDRESSSALES.save(storage, (buyer, sale_id), &true)?;
+
+        let price = self.price.amount;
+        let royalties = self
+            .royalties
+            .iter()
+            .try_fold(Uint128::zero(), |total, (_, amount)| total.checked_add(*amount))?;
+        SALESTATS.update(storage, &self.price.info.to_string(), |stats| -> StdResult<_> {
+            match stats {
+                Some(mut stats) => {
+                    stats.sale_count = add_count(stats.sale_count, 1)?;
+                    stats.volume = stats.volume.checked_add(price)?;
+                    stats.lowest_price = stats.lowest_price.min(price);
+                    stats.highest_price = stats.highest_price.max(price);
+                    stats.last_price = price;
+                    stats.royalties = stats.royalties.checked_add(royalties)?;
+                    Ok(stats)
+                }
+                None => Ok(SaleStats {
+                    asset: self.price.info.clone(),
+                    sale_count: 1,
+                    volume: price,
+                    lowest_price: price,
+                    highest_price: price,
+                    last_price: price,
+                    royalties,
+                }),
+            }
+        })?;
+        Ok(sale_id)
+    }
+
+    fn royalty_payments(&self) -> Vec<RoyaltyPayment> {
+        self.royalties
+            .iter()
+            .map(|(address, amount)| RoyaltyPayment { address: address.to_string(), amount: *amount })
+            .collect()
+    }
+
+    /// payouts of the settlement, empty amounts are skipped
+    pub fn into_msgs<M>(self, querier: &QuerierWrapper) -> StdResult<Vec<CosmosMsg<M>>> {
+        let mut payouts = vec![(self.fee_collector, self.fee)];
+        payouts.extend(self.royalties);
+        payouts.push((self.seller, self.seller_amount));
+        payout_msgs(querier, &self.price.info, payouts)
+    }
+}
+
+/// Every native payout is sent net of its own tax when the config deducts it
+pub(crate) struct NetOfTax {
+    tax_info: Option<TaxInfo>,
+    /// tax of the payouts so far
+    pub tax: Uint128,
+}
+
+impl NetOfTax {
+    pub fn new(querier: &QuerierWrapper, con: &Config, info: &AssetInfo) -> StdResult<Self> {
+        let tax_info = match con.deduct_tax {
+            true => TaxInfo::for_asset(querier, info)?,
+            false => None,
+        };
+        Ok(NetOfTax { tax_info, tax: Uint128::zero() })
+    }
+
+    /// the part of `amount` to send, its tax is added to `tax`
+    pub fn apply(&mut self, amount: Uint128) -> Result<Uint128, ContractError> {
+        let amount_tax = match &self.tax_info {
+            Some(tax_info) => tax_info.compute_tax(amount)?,
+            None => Uint128::zero(),
+        };
+        self.tax = self.tax.checked_add(amount_tax).map_err(|_| ContractError::SettlementOverflow {})?;
+        Ok(amount.checked_sub(amount_tax).map_err(StdError::from)?)
+    }
+}
+
+/// transfers of `info` to each recipient, empty amounts are skipped
+pub(crate) fn payout_msgs<M>(
+    querier: &QuerierWrapper,
+    info: &AssetInfo,
+    payouts: Vec<(Addr, Uint128)>,
+) -> StdResult<Vec<CosmosMsg<M>>> {
+    payouts
+        .into_iter()
+        .filter(|(_, amount)| !amount.is_zero())
+        .map(|(recipient, amount)| {
+            let asset = Asset { info: info.clone(), amount };
+            asset.into_msg(querier, recipient)
+        })
+        .collect()
+}
+
+/// `amount * rate` rounded down, rates above one would pay out more than the amount
+fn share(amount: Uint128, rate: Decimal) -> Result<Uint128, ContractError> {
+    if rate > Decimal::one() {
+        return Err(ContractError::InvalidSettlementRate { rate });
+    }
+    let share = amount.full_mul(rate.atomics()) / Uint256::from(Decimal::one().atomics());
+    Uint128::try_from(share).map_err(|_| ContractError::SettlementOverflow {})
+}
+
+impl From<Settlement> for SimulateSaleResponse {
+    fn from(settlement: Settlement) -> Self {
+        let royalties = settlement.royalty_payments();
+        SimulateSaleResponse {
+            price: settlement.price,
+            fee: settlement.fee,
+            royalties,
+            tax: settlement.tax,
+            seller: settlement.seller.to_string(),
+            seller_amount: settlement.seller_amount,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{Empty, OwnedDeps};
+
+    use crate::asset::AssetInfo;
+    use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg};
+    use crate::state::Cw721Contract;
+    use crate::Extension;
+
+    type Deps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+    fn execute(deps: &mut Deps, msg: ExecuteMsg<Extension>) -> Result<(), ContractError> {
+        Cw721Contract::<Extension, Empty>::default()
+            .execute(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+            .map(|_| ())
+    }
+
+    /// NFT pack 1 of `minter` with a royalty of `royalty_fee`, sold with a `buy_sell_fee` cut
+    fn packed(buy_sell_fee: Decimal, royalty_fee: Decimal) -> Deps {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            name: "nftmx".to_string(),
+            symbol: "NFTMX".to_string(),
+            minter: "minter".to_string(),
+            max_packable_nft: None,
+            max_pack_item_count: None,
+            max_royalty_owner: Some(1),
+            buy_sell_fee: Some(buy_sell_fee),
+            fee_collector: Some("collector".to_string()),
+            pauser: None,
+            pack_metadata_editor: None,
+            // the mock querier has no treasury module
+            deduct_tax: Some(false),
+            sale_denom: None,
+            max_royalty_fee: None,
+        };
+        Cw721Contract::<Extension, Empty>::default()
+            .instantiate(deps.as_mut(), mock_env(), mock_info("minter", &[]), msg)
+            .unwrap();
+        execute(&mut deps, ExecuteMsg::MintPackable(MintMsg {
+            token_id: "t1".to_string(),
+            owner: "minter".to_string(),
+            token_uri: "ipfs://t1".to_string(),
+            extension: None,
+            name: "first".to_string(),
+            price: Uint128::new(500),
+            royalty_fee: None,
+        })).unwrap();
+        execute(&mut deps, ExecuteMsg::PackNfts {
+            token_ids: vec!["t1".to_string()],
+            pack_name: "pack".to_string(),
+            price: Uint128::new(1000),
+            royalty_fee,
+            metadata: None,
+        }).unwrap();
+        deps
+    }
+
+    fn settle(deps: &Deps, amount: u128) -> Result<Settlement, ContractError> {
+        let price = Asset { info: AssetInfo::NativeToken { denom: "uusd".to_string() }, amount: Uint128::new(amount) };
+        Settlement::new(deps.as_ref(), &SaleItem::NftPack { pack_id: 1 }, price)
+    }
+
+    #[test]
+    fn fee_royalty_and_seller_share_the_price() {
+        let deps = packed(Decimal::permille(25), Decimal::percent(5));
+        let settlement = settle(&deps, 1000).unwrap();
+        assert_eq!(settlement.fee_collector, Addr::unchecked("collector"));
+        assert_eq!(settlement.fee, Uint128::new(25));
+        assert_eq!(settlement.royalties, vec![(Addr::unchecked("minter"), Uint128::new(50))]);
+        assert_eq!(settlement.seller, Addr::unchecked("minter"));
+        assert_eq!(settlement.seller_amount, Uint128::new(925));
+        assert_eq!(settlement.tax, Uint128::zero());
+    }
+
+    #[test]
+    fn rounding_dust_goes_to_the_seller() {
+        let deps = packed(Decimal::permille(25), Decimal::percent(5));
+        let settlement = settle(&deps, 999).unwrap();
+        // 24.975 and 49.95 round down
+        assert_eq!(settlement.fee, Uint128::new(24));
+        assert_eq!(settlement.royalties[0].1, Uint128::new(49));
+        assert_eq!(settlement.seller_amount, Uint128::new(926));
+    }
+
+    #[test]
+    fn royalties_only_apply_to_the_gain() {
+        let mut deps = packed(Decimal::permille(25), Decimal::percent(5));
+        let mut pack = all_nft_packs().load(&deps.storage, 1).unwrap();
+        pack.previous_price = Uint128::new(600);
+        all_nft_packs().save(&mut deps.storage, 1, &pack).unwrap();
+
+        let settlement = settle(&deps, 1000).unwrap();
+        assert_eq!(settlement.fee, Uint128::new(25));
+        assert_eq!(settlement.royalties[0].1, Uint128::new(20));
+        assert_eq!(settlement.seller_amount, Uint128::new(955));
+
+        // no gain, no royalty
+        let settlement = settle(&deps, 500).unwrap();
+        assert_eq!(settlement.royalties[0].1, Uint128::zero());
+        assert_eq!(settlement.seller_amount, Uint128::new(488));
+    }
+
+    #[test]
+    fn payouts_above_the_price_fail() {
+        let mut deps = packed(Decimal::permille(25), Decimal::percent(5));
+        ROYALTYFEES.save(&mut deps.storage, (1, "minter"), &Decimal::one()).unwrap();
+        let err = settle(&deps, 1000).unwrap_err();
+        assert_eq!(err, ContractError::PayoutsExceedPrice { price: Uint128::new(1000), payouts: Uint128::new(1025) });
+    }
+
+    #[test]
+    fn empty_payouts_are_skipped() {
+        let deps = packed(Decimal::zero(), Decimal::percent(5));
+        let settlement = settle(&deps, 1000).unwrap();
+        assert_eq!(settlement.fee, Uint128::zero());
+        let msgs: Vec<CosmosMsg<Empty>> = settlement.into_msgs(&deps.as_ref().querier).unwrap();
+        // the royalty and the seller amount
+        assert_eq!(msgs.len(), 2);
+    }
+
+    #[test]
+    fn fee_raise_has_to_fit_every_chain() {
+        let mut deps = packed(Decimal::permille(25), Decimal::percent(95));
+        let update = |buy_sell_fee| ExecuteMsg::UpdateConfig {
+            buy_sell_fee: Some(buy_sell_fee),
+            fee_collector: None,
+            max_packable_nft: None,
+            max_pack_item_count: None,
+            max_royalty_owner: None,
+            pack_metadata_editor: None,
+            deduct_tax: None,
+            sale_denom: None,
+            max_royalty_fee: Some(Decimal::percent(50)),
+        };
+        let err = execute(&mut deps, update(Decimal::percent(10))).unwrap_err();
+        assert_eq!(err, ContractError::RoyaltyChainTooHigh { total: Decimal::percent(105) });
+        execute(&mut deps, update(Decimal::percent(5))).unwrap();
+        assert_eq!(settle(&deps, 1000).unwrap().seller_amount, Uint128::zero());
+    }
+}
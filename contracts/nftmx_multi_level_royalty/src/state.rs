@@ -2,11 +2,17 @@ use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
-use cosmwasm_std::{Addr, BlockInfo, StdResult, Storage, Decimal, Uint128};
+use cosmwasm_std::{
+    Addr, BlockInfo, OverflowError, OverflowOperation, StdError, StdResult, Storage, Decimal, Timestamp, Uint128,
+};
 
 use cw721::{ContractInfoResponse, CustomMsg, Cw721, Expiration};
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex };
 
+use crate::asset::{Asset, AssetInfo};
+use crate::metadata::Trait;
+use crate::msg::{MintMsg, PackKind, RoyaltyPayment, SaleItem};
+
 pub struct Cw721Contract<'a, T, C>
 where
     T: Serialize + DeserializeOwned + Clone,
@@ -17,6 +23,8 @@ where
     /// Stored as (granter, operator) giving operator full control over granter's account
     pub operators: Map<'a, (&'a Addr, &'a Addr), Expiration>,
     pub tokens: IndexedMap<'a, &'a str, TokenInfo<T>, TokenIndexes<'a, T>>,
+    /// Metadata registered for public minting, minted in order of the key
+    pub mint_queue: Map<'a, u64, MintMsg<T>>,
     pub(crate) _custom_response: PhantomData<C>,
 }
 
@@ -40,6 +48,7 @@ where
             "operators",
             "tokens",
             "tokens__owner",
+            "mint_queue",
         )
     }
 }
@@ -55,6 +64,7 @@ where
         operator_key: &'a str,
         tokens_key: &'a str,
         tokens_owner_key: &'a str,
+        mint_queue_key: &'a str,
     ) -> Self {
         let indexes = TokenIndexes {
             owner: MultiIndex::new(token_owner_idx, tokens_key, tokens_owner_key),
@@ -65,6 +75,7 @@ where
             token_count: Item::new(token_count_key),
             operators: Map::new(operator_key),
             tokens: IndexedMap::new(tokens_key, indexes),
+            mint_queue: Map::new(mint_queue_key),
             _custom_response: PhantomData,
         }
     }
@@ -74,13 +85,19 @@ where
     }
 
     pub fn increment_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
-        let val = self.token_count(storage)? + 1;
+        let count = self.token_count(storage)?;
+        let val = count
+            .checked_add(1)
+            .ok_or_else(|| StdError::overflow(OverflowError::new(OverflowOperation::Add, count, 1)))?;
         self.token_count.save(storage, &val)?;
         Ok(val)
     }
 
     pub fn decrement_tokens(&self, storage: &mut dyn Storage) -> StdResult<u64> {
-        let val = self.token_count(storage)? - 1;
+        let count = self.token_count(storage)?;
+        let val = count
+            .checked_sub(1)
+            .ok_or_else(|| StdError::overflow(OverflowError::new(OverflowOperation::Sub, count, 1)))?;
         self.token_count.save(storage, &val)?;
         Ok(val)
     }
@@ -92,9 +109,69 @@ pub struct Config {
     pub collection_name_symbol: String,
     pub max_packable_nft: u64,
     pub max_pack_item_count: u64,
+    /// Longest royalty chain of a pack, owners past it still receive the pack but don't join the chain
     pub max_royalyty_owner: u64,
     pub buy_sell_fee: Decimal,
-    pub contract_owner: Addr
+    pub contract_owner: Addr,
+    /// Receives the buy/sell fee, may differ from the contract owner
+    pub fee_collector: Addr,
+    /// Who may edit the metadata of an NFT pack
+    #[serde(default)]
+    pub pack_metadata_editor: PackMetadataEditor,
+    /// Pay native payouts net of the Terra stability tax, off on chains without the tax
+    #[serde(default = "default_deduct_tax")]
+    pub deduct_tax: bool,
+    /// Denom of pack prices, the only coin accepted when buying a pack
+    #[serde(default = "default_sale_denom")]
+    pub sale_denom: String,
+    /// Upper bound of every royalty rate, a full royalty chain at this rate plus the buy/sell fee fits the price
+    pub max_royalty_fee: Decimal,
+}
+
+/// Highest buy/sell fee the contract owner can set
+pub const MAX_BUY_SELL_FEE_PERCENT: u64 = 10;
+
+/// Terra taxes native payouts, so a config that doesn't say otherwise deducts the tax like `instantiate`
+pub fn default_deduct_tax() -> bool {
+    true
+}
+
+pub fn default_sale_denom() -> String {
+    "uusd".to_string()
+}
+
+/// The highest royalty rate at which `max_royalty_owner` royalties and the buy/sell fee still fit the price
+pub fn max_royalty_fee_bound(buy_sell_fee: Decimal, max_royalty_owner: u64) -> Decimal {
+    (Decimal::one() - buy_sell_fee) / Uint128::from(max_royalty_owner.max(1))
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PackMetadataEditor {
+    /// The current owner of the pack
+    #[default]
+    Owner,
+    /// The account that opened the pack
+    Packer,
+    OwnerOrPacker,
+}
+
+impl PackMetadataEditor {
+    /// whether `sender` may edit a pack of `current_owner` opened by `minted_by`
+    pub fn can_edit(&self, current_owner: &Addr, minted_by: &Addr, sender: &Addr) -> bool {
+        match self {
+            PackMetadataEditor::Owner => current_owner == sender,
+            PackMetadataEditor::Packer => minted_by == sender,
+            PackMetadataEditor::OwnerOrPacker => current_owner == sender || minted_by == sender,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipProposal {
+    /// The proposed contract owner, who must accept before `expires`
+    pub owner: Addr,
+    pub expires: Expiration,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -158,7 +235,10 @@ pub struct PackableToken {
     pub previous_owner: Option<Addr>,
     pub price: Uint128,
     pub number_of_transfers: Uint128,
-    pub for_sale: bool
+    pub for_sale: bool,
+    /// Share of each sale owed to `minted_by`
+    #[serde(default)]
+    pub royalty_fee: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -175,21 +255,154 @@ pub struct NftPack {
     pub number_of_transfers: u64,
     pub for_sale: bool,
     pub royalty_owners: Vec<Addr>,
-    pub approvals: Vec<Addr>
+    pub approvals: Vec<Addr>,
+    #[serde(default)]
+    pub metadata: PackMetadata,
+}
+
+/// Display information of a pack, set by the packer and editable per `PackMetadataEditor`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct PackMetadata {
+    pub description: Option<String>,
+    pub cover_image: Option<String>,
+    /// Lowercase category tags, indexed in `PACKTAGS` or `TOKENPACKTAGS`
+    pub tags: Vec<String>,
+    pub attributes: Vec<Trait>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinterInfo {
+    /// Maximum number of NFTs the minter may create, unlimited if unset
+    pub quota: Option<u64>,
+    pub minted: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintPhase {
+    /// The phase opens once `start` is expired
+    pub start: Expiration,
+    /// The phase closes once `end` is expired
+    pub end: Expiration,
+    pub price: Asset,
+    /// Maximum number of mints per address in this phase, unlimited if unset
+    pub per_wallet_limit: Option<u64>,
+    /// Only addresses in the phase allowlist can mint when set
+    pub allowlist_only: bool,
+    /// Hex encoded root of a merkle allowlist, see `merkle::allowlist_leaf`.
+    /// When set, minters prove their allowance, which also caps their mints in the phase
+    pub merkle_root: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CreatorShare {
+    pub address: Addr,
+    /// Part of the public mint proceeds, the rest goes to the contract owner
+    pub share: Decimal,
+}
+
+/// A drop minted behind a placeholder URI and revealed later.
+/// The admin commits to a seed and the base URI before minting, the reveal offset is derived
+/// from the seed and the reveal block. Only queue entries reserved before reveal mint into it afterwards
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HiddenDrop {
+    pub placeholder_uri: String,
+    /// Hex encoded sha256 of the reveal seed followed by the base URI
+    pub seed_commitment: String,
+    pub drop_size: u64,
+    pub minted: u64,
+    /// Queue entries holding a drop index, they are minted behind the placeholder even after reveal
+    #[serde(default)]
+    pub reserved: u64,
+    /// Set on reveal, the token with drop index `i` gets `{base_uri}{(i + offset) % drop_size}`
+    pub base_uri: Option<String>,
+    pub offset: Option<u64>,
+}
+
+impl HiddenDrop {
+    pub fn revealed_uri(&self, index: u64) -> Option<String> {
+        match (&self.base_uri, self.offset) {
+            (Some(base_uri), Some(offset)) => {
+                Some(format!("{}{}", base_uri, (index + offset) % self.drop_size))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Operations the pauser can halt, all running by default.
+/// Withdrawing pack contents is never paused
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PauseState {
+    pub minting: bool,
+    pub packing: bool,
+    pub trading: bool,
+    pub transfers: bool,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
+pub const MINTERS: Map<&Addr, MinterInfo> = Map::new("minters");
+pub const PAUSER: Item<Addr> = Item::new("pauser");
+
+pub const MINTPHASECOUNTER: Item<u64> = Item::new("mint_phase_counter");
+pub const MINTPHASES: Map<u64, MintPhase> = Map::new("mint_phases");
+pub const MINTPHASEALLOWLIST: Map<(u64, &Addr), bool> = Map::new("mint_phase_allowlist");
+pub const MINTPHASEMINTED: Map<(u64, &Addr), u64> = Map::new("mint_phase_minted");
+pub const CREATORSHARES: Item<Vec<CreatorShare>> = Item::new("creator_shares");
+/// next key of `Cw721Contract::mint_queue` to mint and to register
+pub const MINTQUEUEHEAD: Item<u64> = Item::new("mint_queue_head");
+pub const MINTQUEUETAIL: Item<u64> = Item::new("mint_queue_tail");
+/// token ids waiting in the mint queue, reserved so they can't be minted twice
+pub const MINTQUEUETOKENIDS: Map<&str, bool> = Map::new("mint_queue_token_ids");
+/// queue keys of entries holding a slot of the hidden drop, queued without their URI
+pub const MINTQUEUEHIDDEN: Map<u64, bool> = Map::new("mint_queue_hidden");
+
+pub const HIDDENDROP: Item<HiddenDrop> = Item::new("hidden_drop");
+/// drop index of every token still holding the placeholder URI
+pub const HIDDENTOKENS: Map<&str, u64> = Map::new("hidden_tokens");
+/// revealed URI of tokens that kept the placeholder because another token already had that URI,
+/// until the contract owner gives them a free one
+pub const REVEALCONFLICTS: Map<&str, String> = Map::new("reveal_conflicts");
+pub const PAUSESTATE: Item<PauseState> = Item::new("pause_state");
+pub const OWNERSHIPPROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 pub const NFTPACKCOUNTER: Item<u64> = Item::new("nft_pack_counter");
 
+/// Registry key of an NFT or pack name, names are unique regardless of case
+pub fn name_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
 pub const ALLPACKABLENFTS: Map<&str, PackableToken> = Map::new("app_packable_nfts");
 pub const TOKENURIEXISTS: Map<&str, bool> = Map::new("token_uri_exists");
+/// Name registries are keyed by `name_key`, a key is removed once its name is free again
 pub const TOKENNAMEEXISTS: Map<&str, bool> = Map::new("token_name_exists");
 
 pub const PACKNAMEEXISTS: Map<&str, bool> = Map::new("pack_name_exists");
 
-pub const ROYALTYFEES: Map<(&str, &str), Decimal> = Map::new("royalty_fees");
-pub const ALLNFTPACKS: Map<&str, NftPack> = Map::new("all_nft_packs");
-pub const NFTPACKBALANCES: Map<&str, u64> = Map::new("nft_pack_balances");
+/// (pack id, royalty owner)
+pub const ROYALTYFEES: Map<(u64, &str), Decimal> = Map::new("royalty_fees");
+
+pub struct NftPackIndexes<'a> {
+    pub current_owner: MultiIndex<'a, Addr, NftPack, u64>,
+    pub minted_by: MultiIndex<'a, Addr, NftPack, u64>,
+}
+
+impl<'a> IndexList<NftPack> for NftPackIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<NftPack>> + '_> {
+        let v: Vec<&dyn Index<NftPack>> = vec![&self.current_owner, &self.minted_by];
+        Box::new(v.into_iter())
+    }
+}
+
+/// All NFT packs by id, the owner and minter indexes replace the stored pack balances
+pub fn all_nft_packs<'a>() -> IndexedMap<'a, u64, NftPack, NftPackIndexes<'a>> {
+    let indexes = NftPackIndexes {
+        current_owner: MultiIndex::new(|p| p.current_owner.clone(), "all_nft_packs", "all_nft_packs__current_owner"),
+        minted_by: MultiIndex::new(|p| p.minted_by.clone(), "all_nft_packs", "all_nft_packs__minted_by"),
+    };
+    IndexedMap::new("all_nft_packs", indexes)
+}
+/// Secondary index of NFT packs by (tag, pack_id)
+pub const PACKTAGS: Map<(&str, u64), bool> = Map::new("pack_tags");
 
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -206,12 +419,275 @@ pub struct TokenPack {
     pub number_of_transfers: u64,
     pub for_sale: bool,
     pub royalty_owners: Vec<Addr>,
-    pub approvals: Vec<Addr>
+    pub approvals: Vec<Addr>,
+    #[serde(default)]
+    pub metadata: PackMetadata,
+}
+
+pub struct TokenPackIndexes<'a> {
+    pub current_owner: MultiIndex<'a, Addr, TokenPack, u64>,
+    pub minted_by: MultiIndex<'a, Addr, TokenPack, u64>,
+}
+
+impl<'a> IndexList<TokenPack> for TokenPackIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<TokenPack>> + '_> {
+        let v: Vec<&dyn Index<TokenPack>> = vec![&self.current_owner, &self.minted_by];
+        Box::new(v.into_iter())
+    }
+}
+
+/// All token packs, indexed like `all_nft_packs`
+pub fn all_token_packs<'a>() -> IndexedMap<'a, u64, TokenPack, TokenPackIndexes<'a>> {
+    let indexes = TokenPackIndexes {
+        current_owner: MultiIndex::new(|p| p.current_owner.clone(), "all_token_packs", "all_token_packs__current_owner"),
+        minted_by: MultiIndex::new(|p| p.minted_by.clone(), "all_token_packs", "all_token_packs__minted_by"),
+    };
+    IndexedMap::new("all_token_packs", indexes)
 }
 
-pub const ALLTOKENPACKS: Map<&str, TokenPack> = Map::new("all_token_packs");
 pub const TOKENPACKCOUNTER: Item<u64> = Item::new("token_pack_counter");
 pub const TOKENPACKNAMEEXISTS: Map<&str, bool> = Map::new("token_pack_name_exists");
-pub const TOKENPACKBALANCES: Map<&str, u64> = Map::new("token_pack_balances");
-pub const TOKENROYALTYFEES: Map<(&str, &str), Decimal> = Map::new("token_royalty_fees");
+/// Secondary index of token packs by (tag, pack_id)
+pub const TOKENPACKTAGS: Map<(&str, u64), bool> = Map::new("token_pack_tags");
+pub const TOKENROYALTYFEES: Map<(u64, &str), Decimal> = Map::new("token_royalty_fees");
+/// (pack id, royalty owner) to the address receiving the owner's royalties, if not the owner.
+/// Rates and recipients are keyed by the owner, so the chain position never changes
+pub const ROYALTYRECIPIENTS: Map<(u64, &str), Addr> = Map::new("royalty_recipients");
+pub const TOKENROYALTYRECIPIENTS: Map<(u64, &str), Addr> = Map::new("token_royalty_recipients");
+
+/// per (pack id, royalty owner) storage of a pack kind
+pub type PackRoyaltyMap<T> = Map<'static, (u64, &'static str), T>;
+
+/// royalty rate and recipient maps of a pack kind
+pub fn royalty_maps(kind: PackKind) -> (PackRoyaltyMap<Decimal>, PackRoyaltyMap<Addr>) {
+    match kind {
+        PackKind::Nft => (ROYALTYFEES, ROYALTYRECIPIENTS),
+        PackKind::Token => (TOKENROYALTYFEES, TOKENROYALTYRECIPIENTS),
+    }
+}
+
+/// where the royalties of `owner` on a pack are paid
+pub fn royalty_recipient(storage: &dyn Storage, kind: PackKind, pack_id: u64, owner: &Addr) -> StdResult<Addr> {
+    let (_, recipients) = royalty_maps(kind);
+    Ok(recipients
+        .may_load(storage, (pack_id, owner.as_str()))?
+        .unwrap_or_else(|| owner.clone()))
+}
+
+/// highest sum of royalty rates a pack's chain reached. Rates on a chain are never raised, so every
+/// chain fits a buy/sell fee up to what this leaves
+pub const MAXCHAINROYALTY: Item<Decimal> = Item::new("max_chain_royalty");
+
+/// sum of two rates, an overflow is an error instead of a panic
+pub fn add_rates(a: Decimal, b: Decimal) -> StdResult<Decimal> {
+    Ok(Decimal::new(a.atomics().checked_add(b.atomics())?))
+}
+
+/// sum of the royalty rates of a chain
+pub fn chain_royalty(storage: &dyn Storage, kind: PackKind, pack_id: u64, royalty_owners: &[Addr]) -> StdResult<Decimal> {
+    let (royalty_fees, _) = royalty_maps(kind);
+    royalty_owners.iter().try_fold(Decimal::zero(), |total, owner| {
+        add_rates(total, royalty_fees.load(storage, (pack_id, owner.as_str()))?)
+    })
+}
+
+/// raises `MAXCHAINROYALTY` to the royalty total of a chain
+pub fn record_chain_royalty(storage: &mut dyn Storage, total: Decimal) -> StdResult<()> {
+    if total > MAXCHAINROYALTY.may_load(storage)?.unwrap_or_default() {
+        MAXCHAINROYALTY.save(storage, &total)?;
+    }
+    Ok(())
+}
+
+/// A completed sale, with the amounts as they were paid out
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SaleRecord {
+    pub sale_id: u64,
+    pub item: SaleItem,
+    pub seller: Addr,
+    pub buyer: Addr,
+    pub price: Asset,
+    pub fee: Uint128,
+    /// royalties in chain order
+    pub royalties: Vec<RoyaltyPayment>,
+    pub tax: Uint128,
+    pub seller_amount: Uint128,
+    pub block_height: u64,
+    pub block_time: Timestamp,
+}
+
+/// Sales of the collection paid in one asset
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SaleStats {
+    pub asset: AssetInfo,
+    pub sale_count: u64,
+    pub volume: Uint128,
+    pub lowest_price: Uint128,
+    pub highest_price: Uint128,
+    pub last_price: Uint128,
+    /// royalties paid out, net of tax
+    pub royalties: Uint128,
+}
+
+pub const SALECOUNTER: Item<u64> = Item::new("sale_counter");
+/// append only, keyed by sale id
+pub const SALES: Map<u64, SaleRecord> = Map::new("sales");
+/// (SaleItem::key, sale id)
+pub const ITEMSALES: Map<(&str, u64), bool> = Map::new("item_sales");
+/// (buyer or seller, sale id)
+pub const ADDRESSSALES: Map<(&Addr, u64), bool> = Map::new("address_sales");
+/// keyed by the price asset
+pub const SALESTATS: Map<&str, SaleStats> = Map::new("sale_stats");
+
+/// Collection aggregates kept up to date by every state change, so `CollectionStats` never scans.
+/// NFTs inside a pack are held through the pack, this contract is never counted as a holder
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct CollectionCounters {
+    pub nft_packs: u64,
+    pub token_packs: u64,
+    pub items_listed: u64,
+    pub unique_holders: u64,
+}
+
+pub const COLLECTIONCOUNTERS: Item<CollectionCounters> = Item::new("collection_counters");
+/// number of NFTs held outside packs plus packs, per holder
+pub const HOLDINGS: Map<&Addr, u64> = Map::new("holdings");
+/// listed price of every item for sale by SaleItem::key, in the sale denom at listing time
+pub const LISTINGS: Map<&str, Asset> = Map::new("listings");
+/// items for sale by (denom, price, SaleItem::key), the first key of a denom is its floor
+pub const LISTEDPRICES: Map<(&str, u128, &str), bool> = Map::new("listed_prices");
+/// number of listings per denom
+pub const LISTEDDENOMS: Map<&str, u64> = Map::new("listed_denoms");
+
+pub(crate) fn add_count(count: u64, delta: u64) -> StdResult<u64> {
+    count
+        .checked_add(delta)
+        .ok_or_else(|| StdError::overflow(OverflowError::new(OverflowOperation::Add, count, delta)))
+}
+
+fn sub_count(count: u64, delta: u64) -> StdResult<u64> {
+    count
+        .checked_sub(delta)
+        .ok_or_else(|| StdError::overflow(OverflowError::new(OverflowOperation::Sub, count, delta)))
+}
+
+fn update_counters<F>(storage: &mut dyn Storage, action: F) -> StdResult<CollectionCounters>
+where
+    F: FnOnce(CollectionCounters) -> StdResult<CollectionCounters>,
+{
+    let counters = action(COLLECTIONCOUNTERS.may_load(storage)?.unwrap_or_default())?;
+    COLLECTIONCOUNTERS.save(storage, &counters)?;
+    Ok(counters)
+}
+
+pub fn add_holdings(storage: &mut dyn Storage, holder: &Addr, count: u64) -> StdResult<()> {
+    if count == 0 {
+        return Ok(());
+    }
+    let held = HOLDINGS.may_load(storage, holder)?.unwrap_or_default();
+    HOLDINGS.save(storage, holder, &add_count(held, count)?)?;
+    if held == 0 {
+        update_counters(storage, |mut c| {
+            c.unique_holders = add_count(c.unique_holders, 1)?;
+            Ok(c)
+        })?;
+    }
+    Ok(())
+}
+
+pub fn remove_holdings(storage: &mut dyn Storage, holder: &Addr, count: u64) -> StdResult<()> {
+    if count == 0 {
+        return Ok(());
+    }
+    let held = HOLDINGS.may_load(storage, holder)?.unwrap_or_default();
+    let left = sub_count(held, count)?;
+    if left == 0 {
+        HOLDINGS.remove(storage, holder);
+        update_counters(storage, |mut c| {
+            c.unique_holders = sub_count(c.unique_holders, 1)?;
+            Ok(c)
+        })?;
+    } else {
+        HOLDINGS.save(storage, holder, &left)?;
+    }
+    Ok(())
+}
+
+pub fn move_holdings(storage: &mut dyn Storage, from: &Addr, to: &Addr, count: u64) -> StdResult<()> {
+    if from == to {
+        return Ok(());
+    }
+    remove_holdings(storage, from, count)?;
+    add_holdings(storage, to, count)
+}
+
+/// Listing price of an item, `None` when it is not for sale
+pub fn listed_price(for_sale: bool, price: Uint128) -> Option<Uint128> {
+    for_sale.then_some(price)
+}
+
+/// Lists an item at `price` in the current sale denom, or delists it when `None`
+pub fn update_listing(storage: &mut dyn Storage, item: &SaleItem, price: Option<Uint128>) -> StdResult<()> {
+    let key = item.key();
+    let old = LISTINGS.may_load(storage, &key)?;
+    if let Some(listing) = &old {
+        let denom = listing.info.to_string();
+        LISTEDPRICES.remove(storage, (&denom, listing.amount.u128(), &key));
+        match sub_count(LISTEDDENOMS.load(storage, &denom)?, 1)? {
+            0 => LISTEDDENOMS.remove(storage, &denom),
+            count => LISTEDDENOMS.save(storage, &denom, &count)?,
+        }
+        LISTINGS.remove(storage, &key);
+    }
+    if let Some(amount) = price {
+        let listing = Asset {
+            info: AssetInfo::NativeToken { denom: CONFIG.load(storage)?.sale_denom },
+            amount,
+        };
+        let denom = listing.info.to_string();
+        LISTEDPRICES.save(storage, (&denom, amount.u128(), &key), &true)?;
+        let count = LISTEDDENOMS.may_load(storage, &denom)?.unwrap_or_default();
+        LISTEDDENOMS.save(storage, &denom, &add_count(count, 1)?)?;
+        LISTINGS.save(storage, &key, &listing)?;
+    }
+    match (old, price) {
+        (None, Some(_)) => update_counters(storage, |mut c| {
+            c.items_listed = add_count(c.items_listed, 1)?;
+            Ok(c)
+        }).map(|_| ()),
+        (Some(_), None) => update_counters(storage, |mut c| {
+            c.items_listed = sub_count(c.items_listed, 1)?;
+            Ok(c)
+        }).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+/// mirrors a new cw721 owner of a token in its packable record, the contract while it is packed
+pub fn set_packable_owner(storage: &mut dyn Storage, token_id: &str, owner: &Addr) -> StdResult<()> {
+    ALLPACKABLENFTS.update(storage, token_id, |old| -> StdResult<_> {
+        let mut packable_token = old.ok_or_else(|| StdError::not_found("PackableToken"))?;
+        if packable_token.current_owner != *owner {
+            packable_token.previous_owner = Some(std::mem::replace(&mut packable_token.current_owner, owner.clone()));
+        }
+        Ok(packable_token)
+    })?;
+    Ok(())
+}
+
+/// Counts a pack opened or closed
+pub fn update_pack_count(storage: &mut dyn Storage, kind: PackKind, opened: bool) -> StdResult<()> {
+    update_counters(storage, |mut c| {
+        let count = match kind {
+            PackKind::Nft => &mut c.nft_packs,
+            PackKind::Token => &mut c.token_packs,
+        };
+        *count = match opened {
+            true => add_count(*count, 1)?,
+            false => sub_count(*count, 1)?,
+        };
+        Ok(c)
+    })?;
+    Ok(())
+}
 